@@ -1,16 +1,25 @@
 //! Agent configuration and persistence.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use kdl::{KdlDocument, KdlNode};
 use serde::{Deserialize, Serialize};
 
+use crate::config::Config;
 use crate::error::{MaestroError, MaestroResult};
+use crate::layout::parse_layout;
+use crate::placement::{Placement, PlacementKind};
+
+/// Node of `agents.kdl` standing for an unmodified built-in agent.
+const DEFAULT_AGENT_NODE: &str = "default";
+
+/// Separator between agent and variant in display names (`claude › yolo`).
+pub const VARIANT_SEPARATOR: &str = " › ";
 
 /// An AI coding agent configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Agent {
     /// Display name for the agent.
     pub name: String,
@@ -22,6 +31,62 @@ pub struct Agent {
     /// Optional description or notes.
     #[serde(default)]
     pub note: Option<String>,
+    /// Hidden agents stay configured but are left out of the agent picker.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Argument presets offered alongside the base agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<AgentVariant>,
+    /// Arguments appended to the command to continue the previous conversation
+    /// when an exited pane is respawned (e.g. `--continue`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resume_args: Vec<String>,
+    /// Where new panes of this agent go, overriding the global default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+    /// KDL layout body opened as a new tab, with `agent` nodes marking
+    /// where the agent runs (see [`crate::layout`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+}
+
+/// A named preset layered on top of a base agent's command.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentVariant {
+    /// Variant name, unique within its agent.
+    pub name: String,
+    /// Arguments appended to (or replacing) the base agent's args.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Replace the base args instead of appending to them.
+    #[serde(default)]
+    pub replace_args: bool,
+    /// Environment variables set for the command (via `env`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl Agent {
+    /// Look up a variant by name, ignoring case.
+    pub fn find_variant(&self, name: &str) -> Option<&AgentVariant> {
+        self.variants.iter().find(|v| names_match(&v.name, name))
+    }
+
+    /// Name shown for this agent or one of its variants.
+    pub fn display_name(&self, variant: Option<&AgentVariant>) -> String {
+        match variant {
+            Some(v) => format!("{}{VARIANT_SEPARATOR}{}", self.name, v.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Split a display name into agent and optional variant name.
+pub fn split_variant_name(name: &str) -> (&str, Option<&str>) {
+    match name.split_once(VARIANT_SEPARATOR) {
+        Some((agent, variant)) => (agent.trim(), Some(variant.trim())),
+        None => (name.trim(), None),
+    }
 }
 
 /// Runtime status of an agent pane.
@@ -34,6 +99,18 @@ pub enum PaneStatus {
     Exited(Option<i32>),
 }
 
+/// Whether an agent's executable could be found on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Availability {
+    /// Not checked yet, or the check is still in flight.
+    #[default]
+    Unknown,
+    /// The executable resolved on the host `PATH`.
+    Available,
+    /// The executable could not be found.
+    Missing,
+}
+
 /// A running instance of an agent in a Zellij pane.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct AgentPane {
@@ -49,8 +126,15 @@ pub struct AgentPane {
     pub workspace_path: String,
     /// Name of the agent configuration.
     pub agent_name: String,
+    /// Agents the pane could belong to when its command matched several
+    /// equally well; `agent_name` stays empty until resolved.
+    pub candidates: Vec<String>,
     /// Current execution status.
     pub status: PaneStatus,
+    /// Whether the pane floats above the tiled layout.
+    pub floating: bool,
+    /// Whether the floating pane stays on top when unfocused.
+    pub pinned: bool,
 }
 
 /// Load agents from a KDL configuration file.
@@ -66,6 +150,17 @@ pub fn load_agents(path: &Path) -> MaestroResult<Vec<Agent>> {
         }
     };
 
+    parse_agent_nodes(&data, true)
+}
+
+/// Parse and validate `agent` nodes from KDL text, ignoring other nodes.
+pub fn parse_agents_kdl(data: &str) -> MaestroResult<Vec<Agent>> {
+    parse_agent_nodes(data, false)
+}
+
+/// Parse `agent` nodes and, with `resolve_defaults`, the `default` nodes
+/// `agents.kdl` keeps unmodified built-ins in place with.
+fn parse_agent_nodes(data: &str, resolve_defaults: bool) -> MaestroResult<Vec<Agent>> {
     if data.trim().is_empty() {
         return Ok(Vec::new());
     }
@@ -73,21 +168,68 @@ pub fn load_agents(path: &Path) -> MaestroResult<Vec<Agent>> {
     let doc: KdlDocument = data
         .parse()
         .map_err(|e: kdl::KdlError| MaestroError::ConfigParse(e.to_string()))?;
+    let defaults = default_agents();
     let mut agents = Vec::new();
     for node in doc.nodes() {
-        if node.name().value() != "agent" {
-            continue;
+        match node.name().value() {
+            "agent" if resolve_defaults => {
+                agents.push(migrate_legacy_default(agent_from_kdl(node)?, &defaults))
+            }
+            "agent" => agents.push(agent_from_kdl(node)?),
+            DEFAULT_AGENT_NODE if resolve_defaults => {
+                let name = node.entries().first().and_then(|e| e.value().as_string());
+                if let Some(default) = defaults
+                    .iter()
+                    .find(|d| name.is_some_and(|n| names_match(&d.name, n)))
+                {
+                    agents.push(default.clone());
+                }
+            }
+            _ => {}
         }
-        agents.push(agent_from_kdl(node)?);
     }
     validate_agents(&agents)?;
     Ok(agents)
 }
 
-/// Save agents to a KDL configuration file.
+/// Older versions saved the built-ins in full, without `resume_args`. Such an
+/// entry, left as shipped back then, is the current built-in (keeping whether
+/// it was hidden), so it picks up later changes to the definition.
+fn migrate_legacy_default(agent: Agent, defaults: &[Agent]) -> Agent {
+    let Some(default) = defaults.iter().find(|d| d.name == agent.name) else {
+        return agent;
+    };
+    let legacy = Agent {
+        resume_args: Vec::new(),
+        hidden: agent.hidden,
+        ..default.clone()
+    };
+    if agent == legacy {
+        Agent {
+            hidden: agent.hidden,
+            ..default.clone()
+        }
+    } else {
+        agent
+    }
+}
+
+/// Save agents to a KDL configuration file. Unmodified built-ins are saved
+/// as a `default "<name>"` node, keeping their place in the list without
+/// turning them into user agents.
 pub fn save_agents(path: &Path, agents: &[Agent]) -> MaestroResult<()> {
     validate_agents(agents)?;
-    let payload = agents_to_kdl(agents);
+    let mut doc = KdlDocument::new();
+    for agent in agents {
+        if is_unmodified_default(agent) {
+            let mut node = KdlNode::new(DEFAULT_AGENT_NODE);
+            node.push(agent.name.clone());
+            doc.nodes_mut().push(node);
+        } else {
+            doc.nodes_mut().push(agent_to_kdl(agent));
+        }
+    }
+    let payload = doc.to_string();
     fs::write(path, payload.as_bytes()).map_err(|e| MaestroError::FileWrite {
         path: path.to_path_buf(),
         message: e.to_string(),
@@ -108,28 +250,39 @@ pub fn default_agents() -> Vec<Agent> {
             command: "cursor-agent".to_string(),
             args: Vec::new(),
             note: Some("Default agent config".to_string()),
+            ..Default::default()
         },
         Agent {
             name: "claude".to_string(),
             command: "claude".to_string(),
             args: Vec::new(),
             note: Some("Default agent config".to_string()),
+            resume_args: vec!["--continue".to_string()],
+            ..Default::default()
         },
         Agent {
             name: "gemini".to_string(),
             command: "gemini".to_string(),
             args: Vec::new(),
             note: Some("Default agent config".to_string()),
+            ..Default::default()
         },
         Agent {
             name: "codex".to_string(),
             command: "codex".to_string(),
             args: Vec::new(),
             note: Some("Default agent config".to_string()),
+            resume_args: vec!["resume".to_string(), "--last".to_string()],
+            ..Default::default()
         },
     ]
 }
 
+/// Whether `agent` is a built-in default exactly as shipped.
+pub fn is_unmodified_default(agent: &Agent) -> bool {
+    default_agents().contains(agent)
+}
+
 /// Check if an agent name is one of the built-in defaults.
 pub fn is_default_agent(name: &str) -> bool {
     matches!(
@@ -138,26 +291,39 @@ pub fn is_default_agent(name: &str) -> bool {
     )
 }
 
-/// Load agents, merging user config with built-in defaults.
-pub fn load_agents_default() -> MaestroResult<Vec<Agent>> {
+/// Built-ins cannot be deleted while the defaults are merged in, since they
+/// would come back on the next load; with defaults disabled they are ordinary
+/// agents.
+pub fn check_agent_deletable(name: &str, config: &Config) -> MaestroResult<()> {
+    if config.default_agents && is_default_agent(name) {
+        return Err(MaestroError::CannotDeleteDefaultAgent(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Load agents, merging user config with built-in defaults unless the
+/// plugin config disables them.
+pub fn load_agents_default(config: &Config) -> MaestroResult<Vec<Agent>> {
     let path = default_config_path();
     let user_agents = load_agents(&path)?;
+    Ok(merge_with_defaults(user_agents, config))
+}
 
-    let mut merged = default_agents();
-
-    for user_agent in user_agents {
-        if let Some(pos) = merged
-            .iter()
-            .position(|a| names_match(&a.name, &user_agent.name))
-        {
-            merged[pos] = user_agent;
-        } else {
-            merged.push(user_agent);
+/// Keep the user's agents in the order they were saved, which is the order
+/// chosen in the config screen, and append the defaults they do not override.
+/// With defaults disabled, saved built-ins the user never modified are dropped.
+fn merge_with_defaults(user_agents: Vec<Agent>, config: &Config) -> Vec<Agent> {
+    let mut merged = user_agents;
+    if !config.default_agents {
+        merged.retain(|a| !is_unmodified_default(a));
+    } else {
+        for default in default_agents() {
+            if !merged.iter().any(|a| names_match(&a.name, &default.name)) {
+                merged.push(default);
+            }
         }
     }
-
-    merged.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(merged)
+    merged
 }
 
 const MAX_AGENT_NAME_LENGTH: usize = 64;
@@ -168,7 +334,30 @@ pub fn names_match(a: &str, b: &str) -> bool {
     a.eq_ignore_ascii_case(b)
 }
 
-fn validate_agent_name(name: &str) -> MaestroResult<()> {
+/// Derive a name from `base` that does not collide with any existing agent,
+/// appending `-2`, `-3`, ... as needed.
+pub fn unique_agent_name(agents: &[Agent], base: &str) -> String {
+    let base = base.trim();
+    let taken = |candidate: &str| agents.iter().any(|a| names_match(&a.name, candidate));
+    if !taken(base) {
+        return base.to_string();
+    }
+    let mut n = 2;
+    loop {
+        let suffix = format!("-{n}");
+        let stem: String = base
+            .chars()
+            .take(MAX_AGENT_NAME_LENGTH.saturating_sub(suffix.len()))
+            .collect();
+        let candidate = format!("{stem}{suffix}");
+        if !taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+pub(crate) fn validate_agent_name(name: &str) -> MaestroResult<()> {
     if name.chars().any(|c| c.is_control()) {
         return Err(MaestroError::InvalidAgentName(
             "cannot contain control characters".to_string(),
@@ -183,7 +372,7 @@ fn validate_agent_name(name: &str) -> MaestroResult<()> {
     Ok(())
 }
 
-fn validate_agents(agents: &[Agent]) -> MaestroResult<()> {
+pub(crate) fn validate_agents(agents: &[Agent]) -> MaestroResult<()> {
     let mut seen = BTreeSet::new();
     for agent in agents {
         let name = agent.name.trim();
@@ -198,6 +387,38 @@ fn validate_agents(agents: &[Agent]) -> MaestroResult<()> {
         if !seen.insert(normalized) {
             return Err(MaestroError::DuplicateAgentName(name.to_string()));
         }
+        validate_variants(agent)?;
+        if let Some(placement) = &agent.placement {
+            placement.validate()?;
+        }
+        if let Some(layout) = &agent.layout {
+            parse_layout(layout)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_variants(agent: &Agent) -> MaestroResult<()> {
+    let mut seen = BTreeSet::new();
+    for variant in &agent.variants {
+        let name = variant.name.trim();
+        if name.is_empty() {
+            return Err(MaestroError::InvalidAgentConfig(format!(
+                "variant of {} missing name",
+                agent.name
+            )));
+        }
+        if name.contains(VARIANT_SEPARATOR.trim()) || name.chars().any(|c| c.is_control()) {
+            return Err(MaestroError::InvalidAgentConfig(format!(
+                "invalid variant name: {name}"
+            )));
+        }
+        if !seen.insert(name.to_lowercase()) {
+            return Err(MaestroError::InvalidAgentConfig(format!(
+                "duplicate variant {name} of {}",
+                agent.name
+            )));
+        }
     }
     Ok(())
 }
@@ -211,9 +432,17 @@ fn agent_from_kdl(node: &KdlNode) -> MaestroResult<Agent> {
         .get("note")
         .and_then(|e| e.value().as_string())
         .map(|s| s.to_string());
+    let hidden = node
+        .get("hidden")
+        .and_then(|e| e.value().as_bool())
+        .unwrap_or(false);
 
     let mut command = String::new();
     let mut args: Vec<String> = Vec::new();
+    let mut variants = Vec::new();
+    let mut resume_args = Vec::new();
+    let mut placement = None;
+    let mut layout = None;
     if let Some(children) = node.children() {
         for child in children.nodes() {
             match child.name().value() {
@@ -234,14 +463,16 @@ fn agent_from_kdl(node: &KdlNode) -> MaestroResult<Agent> {
                         }
                     }
                 }
-                "args" => {
-                    for entry in child.entries() {
-                        if let Some(s) = entry.value().as_string() {
-                            args.push(s.to_string());
-                        } else {
-                            args.push(entry.value().to_string());
-                        }
-                    }
+                "args" => args.extend(entry_strings(child)),
+                "variant" => variants.push(variant_from_kdl(child)?),
+                "resume" => resume_args.extend(entry_strings(child)),
+                "placement" => placement = Some(placement_from_kdl(child)?),
+                "layout" => {
+                    let mut body = child.children().cloned().unwrap_or_default();
+                    body.fmt();
+                    let body = body.to_string().trim().to_string();
+                    parse_layout(&body)?;
+                    layout = Some(body);
                 }
                 _ => {}
             }
@@ -252,37 +483,188 @@ fn agent_from_kdl(node: &KdlNode) -> MaestroResult<Agent> {
         command,
         args,
         note,
+        hidden,
+        variants,
+        resume_args,
+        placement,
+        layout,
+    })
+}
+
+fn placement_from_kdl(node: &KdlNode) -> MaestroResult<Placement> {
+    let kind = node
+        .entries()
+        .iter()
+        .find(|e| e.name().is_none())
+        .and_then(|e| e.value().as_string())
+        .map(PlacementKind::parse)
+        .transpose()?
+        .unwrap_or_default();
+    let geometry = |key: &str| {
+        node.get(key).map(|e| {
+            e.value()
+                .as_string()
+                .map(str::to_string)
+                .unwrap_or_else(|| e.value().to_string())
+        })
+    };
+    let placement = Placement {
+        kind,
+        x: geometry("x"),
+        y: geometry("y"),
+        width: geometry("width"),
+        height: geometry("height"),
+    };
+    placement.validate()?;
+    Ok(placement)
+}
+
+fn placement_to_kdl(placement: &Placement) -> KdlNode {
+    let mut node = KdlNode::new("placement");
+    node.push(placement.kind.label());
+    for (key, value) in [
+        ("x", &placement.x),
+        ("y", &placement.y),
+        ("width", &placement.width),
+        ("height", &placement.height),
+    ] {
+        if let Some(value) = value {
+            node.insert(key, value.clone());
+        }
+    }
+    node
+}
+
+fn entry_strings(node: &KdlNode) -> impl Iterator<Item = String> + '_ {
+    node.entries().iter().map(|e| {
+        e.value()
+            .as_string()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| e.value().to_string())
     })
 }
 
-fn agents_to_kdl(agents: &[Agent]) -> String {
+fn variant_from_kdl(node: &KdlNode) -> MaestroResult<AgentVariant> {
+    let name = node
+        .get("name")
+        .and_then(|e| e.value().as_string())
+        .ok_or_else(|| MaestroError::InvalidAgentConfig("variant missing name".to_string()))?;
+    let replace_args = node
+        .get("replace_args")
+        .and_then(|e| e.value().as_bool())
+        .unwrap_or(false);
+
+    let mut args = Vec::new();
+    let mut env = BTreeMap::new();
+    if let Some(children) = node.children() {
+        for child in children.nodes() {
+            match child.name().value() {
+                "args" => args.extend(entry_strings(child)),
+                "env" => {
+                    for entry in child.entries() {
+                        if let Some(key) = entry.name() {
+                            let value = entry
+                                .value()
+                                .as_string()
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| entry.value().to_string());
+                            env.insert(key.value().to_string(), value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(AgentVariant {
+        name: name.to_string(),
+        args,
+        replace_args,
+        env,
+    })
+}
+
+pub(crate) fn agents_to_kdl(agents: &[Agent]) -> String {
     let mut doc = KdlDocument::new();
     for agent in agents {
-        let mut node = KdlNode::new("agent");
-        node.insert("name", agent.name.clone());
-        if let Some(note) = &agent.note {
-            node.insert("note", note.clone());
+        doc.nodes_mut().push(agent_to_kdl(agent));
+    }
+    doc.to_string()
+}
+
+fn agent_to_kdl(agent: &Agent) -> KdlNode {
+    let mut node = KdlNode::new("agent");
+    node.insert("name", agent.name.clone());
+    if let Some(note) = &agent.note {
+        node.insert("note", note.clone());
+    }
+    if agent.hidden {
+        node.insert("hidden", true);
+    }
+    let mut children = KdlDocument::new();
+    if !agent.command.trim().is_empty() {
+        let mut cmd_node = KdlNode::new("cmd");
+        cmd_node.push(agent.command.clone());
+        children.nodes_mut().push(cmd_node);
+    }
+    if !agent.args.is_empty() {
+        let mut args_node = KdlNode::new("args");
+        for arg in &agent.args {
+            args_node.push(arg.clone());
         }
-        let mut children = KdlDocument::new();
-        if !agent.command.trim().is_empty() {
-            let mut cmd_node = KdlNode::new("cmd");
-            cmd_node.push(agent.command.clone());
-            children.nodes_mut().push(cmd_node);
+        children.nodes_mut().push(args_node);
+    }
+    if let Some(placement) = &agent.placement {
+        children.nodes_mut().push(placement_to_kdl(placement));
+    }
+    if !agent.resume_args.is_empty() {
+        let mut resume_node = KdlNode::new("resume");
+        for arg in &agent.resume_args {
+            resume_node.push(arg.clone());
         }
-        if !agent.args.is_empty() {
-            let mut args_node = KdlNode::new("args");
-            for arg in &agent.args {
-                args_node.push(arg.clone());
-            }
-            children.nodes_mut().push(args_node);
+        children.nodes_mut().push(resume_node);
+    }
+    for variant in &agent.variants {
+        children.nodes_mut().push(variant_to_kdl(variant));
+    }
+    if let Some(body) = agent.layout.as_deref().and_then(|l| parse_layout(l).ok()) {
+        let mut layout_node = KdlNode::new("layout");
+        layout_node.set_children(body);
+        layout_node.fmt();
+        children.nodes_mut().push(layout_node);
+    }
+    node.set_children(children);
+    node
+}
+
+fn variant_to_kdl(variant: &AgentVariant) -> KdlNode {
+    let mut node = KdlNode::new("variant");
+    node.insert("name", variant.name.clone());
+    if variant.replace_args {
+        node.insert("replace_args", true);
+    }
+    let mut children = KdlDocument::new();
+    if !variant.args.is_empty() {
+        let mut args_node = KdlNode::new("args");
+        for arg in &variant.args {
+            args_node.push(arg.clone());
         }
+        children.nodes_mut().push(args_node);
+    }
+    if !variant.env.is_empty() {
+        let mut env_node = KdlNode::new("env");
+        for (key, value) in &variant.env {
+            env_node.insert(key.as_str(), value.clone());
+        }
+        children.nodes_mut().push(env_node);
+    }
+    if !children.nodes().is_empty() {
         node.set_children(children);
-        doc.nodes_mut().push(node);
     }
-    doc.to_string()
+    node
 }
 
-fn config_base_dir() -> PathBuf {
+pub(crate) fn config_base_dir() -> PathBuf {
     PathBuf::from(format!("{}/.config/maestro", crate::WASI_HOST_MOUNT))
 }
 
@@ -302,12 +684,14 @@ mod tests {
                 command: "echo".to_string(),
                 args: vec!["hello".to_string()],
                 note: Some("Test agent".to_string()),
+                ..Default::default()
             },
             Agent {
                 name: "agent2".to_string(),
                 command: "ls".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
         ];
 
@@ -339,12 +723,14 @@ mod tests {
                 command: "cmd1".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
             Agent {
                 name: "duplicate".to_string(),
                 command: "cmd2".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
         ];
 
@@ -359,12 +745,14 @@ mod tests {
                 command: "cmd1".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
             Agent {
                 name: "duplicate".to_string(),
                 command: "cmd2".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
         ];
 
@@ -383,6 +771,7 @@ mod tests {
             command: "cmd".to_string(),
             args: Vec::new(),
             note: None,
+            ..Default::default()
         }];
         let result = validate_agents(&agents);
         assert!(result.is_err());
@@ -399,6 +788,7 @@ mod tests {
             command: "cmd".to_string(),
             args: Vec::new(),
             note: None,
+            ..Default::default()
         }];
         let result = validate_agents(&agents);
         assert!(result.is_err());
@@ -415,6 +805,7 @@ mod tests {
             command: "cmd".to_string(),
             args: Vec::new(),
             note: None,
+            ..Default::default()
         }];
         assert!(validate_agents(&agents).is_ok());
     }
@@ -439,6 +830,7 @@ mod tests {
             command: "cmd".to_string(),
             args: vec!["arg1".to_string(), "arg2".to_string()],
             note: Some("A test agent with all fields".to_string()),
+            ..Default::default()
         }];
 
         save_agents(path, &agents).unwrap();
@@ -477,6 +869,7 @@ mod tests {
             command: "cmd".to_string(),
             args: Vec::new(),
             note: None,
+            ..Default::default()
         }];
 
         assert!(save_agents(&path, &invalid_agents).is_err());
@@ -544,12 +937,14 @@ agent name="duplicate" {
                 command: "custom-cmd".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
             Agent {
                 name: "Cursor".to_string(),
                 command: "custom-cursor".to_string(),
                 args: Vec::new(),
                 note: None,
+                ..Default::default()
             },
         ];
 
@@ -588,4 +983,333 @@ agent name="duplicate" {
         assert!(merged.iter().any(|a| a.name == "gemini"));
         assert!(merged.iter().any(|a| a.name == "codex"));
     }
+
+    #[test]
+    fn test_save_and_load_hidden_flag() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+
+        let agents = vec![Agent {
+            name: "gemini".to_string(),
+            command: "gemini".to_string(),
+            args: Vec::new(),
+            note: None,
+            hidden: true,
+            ..Default::default()
+        }];
+
+        save_agents(path, &agents).unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        assert!(contents.contains("hidden=true"));
+
+        let loaded = load_agents(path).unwrap();
+        assert!(loaded[0].hidden);
+    }
+
+    #[test]
+    fn test_merge_with_defaults_disabled() {
+        let config = Config {
+            default_agents: false,
+            ..Default::default()
+        };
+        let merged = merge_with_defaults(
+            vec![crate::test_helpers::create_test_agent("custom")],
+            &config,
+        );
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].name, "custom");
+    }
+
+    #[test]
+    fn test_merge_with_defaults_user_override_keeps_hidden() {
+        let mut hidden_claude = crate::test_helpers::create_test_agent("claude");
+        hidden_claude.hidden = true;
+        let merged = merge_with_defaults(vec![hidden_claude], &Config::default());
+        assert_eq!(merged.len(), 4);
+        let claude = merged.iter().find(|a| a.name == "claude").unwrap();
+        assert!(claude.hidden);
+    }
+
+    #[test]
+    fn test_defaults_disabled_after_save() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+        let mut agents = merge_with_defaults(
+            vec![crate::test_helpers::create_test_agent("custom")],
+            &Config::default(),
+        );
+        agents.swap(0, 2);
+        agents[1].hidden = true;
+        save_agents(path, &agents).unwrap();
+
+        // Unmodified built-ins keep their place but are not saved as agents.
+        let contents = std::fs::read_to_string(path).unwrap();
+        assert!(contents.contains(r#"default "claude""#));
+        assert_eq!(load_agents(path).unwrap(), agents);
+
+        let disabled = Config {
+            default_agents: false,
+            ..Default::default()
+        };
+        let mut loaded = merge_with_defaults(load_agents(path).unwrap(), &disabled);
+        let names: Vec<&str> = loaded.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["cursor", "custom"]);
+
+        // The edited built-in left over is an ordinary agent now.
+        assert!(check_agent_deletable("cursor", &disabled).is_ok());
+        assert!(matches!(
+            check_agent_deletable("cursor", &Config::default()),
+            Err(MaestroError::CannotDeleteDefaultAgent(_))
+        ));
+        loaded.remove(0);
+        save_agents(path, &loaded).unwrap();
+        let names: Vec<String> = merge_with_defaults(load_agents(path).unwrap(), &disabled)
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(names, ["custom"]);
+    }
+
+    #[test]
+    fn test_load_migrates_legacy_builtins() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+        // Built-ins as older versions saved them, before `resume_args`.
+        std::fs::write(
+            path,
+            r#"
+agent name="claude" note="Default agent config" {
+    cmd "claude"
+}
+agent name="codex" note="Default agent config" hidden=true {
+    cmd "codex"
+}
+agent name="gemini" note="My gemini" {
+    cmd "gemini"
+}
+"#,
+        )
+        .unwrap();
+
+        let loaded = load_agents(path).unwrap();
+        assert!(is_unmodified_default(&loaded[0]));
+        assert_eq!(loaded[0].resume_args, vec!["--continue"]);
+        assert!(loaded[1].hidden);
+        assert_eq!(loaded[1].resume_args, vec!["resume", "--last"]);
+        // Customised built-ins are left alone.
+        assert_eq!(loaded[2].note.as_deref(), Some("My gemini"));
+        assert!(loaded[2].resume_args.is_empty());
+
+        let disabled = Config {
+            default_agents: false,
+            ..Default::default()
+        };
+        let names: Vec<String> = merge_with_defaults(loaded, &disabled)
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(names, ["codex", "gemini"]);
+    }
+
+    #[test]
+    fn test_merge_with_defaults_keeps_saved_order() {
+        let merged = merge_with_defaults(
+            vec![
+                crate::test_helpers::create_test_agent("zed"),
+                crate::test_helpers::create_test_agent("gemini"),
+                crate::test_helpers::create_test_agent("aider"),
+            ],
+            &Config::default(),
+        );
+        let names: Vec<&str> = merged.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["zed", "gemini", "aider", "cursor", "claude", "codex"]
+        );
+    }
+
+    #[test]
+    fn test_unique_agent_name() {
+        let agents = vec![
+            crate::test_helpers::create_test_agent("claude"),
+            crate::test_helpers::create_test_agent("Claude-2"),
+        ];
+        assert_eq!(unique_agent_name(&agents, "codex"), "codex");
+        assert_eq!(unique_agent_name(&agents, "claude"), "claude-3");
+
+        let long = vec![crate::test_helpers::create_test_agent(&"a".repeat(64))];
+        let renamed = unique_agent_name(&long, &"a".repeat(64));
+        assert_eq!(renamed.chars().count(), 64);
+        assert!(renamed.ends_with("-2"));
+    }
+
+    #[test]
+    fn test_load_agent_variants() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+        let kdl = r#"
+agent name="claude" {
+    cmd "claude"
+    args "--verbose"
+    variant name="yolo" {
+        args "--dangerously-skip-permissions"
+    }
+    variant name="opus" replace_args=true {
+        args "--model" "opus"
+        env MAX_THINKING="1"
+    }
+}
+"#;
+        std::fs::write(path, kdl).unwrap();
+
+        let loaded = load_agents(path).unwrap();
+        let claude = &loaded[0];
+        assert_eq!(claude.variants.len(), 2);
+        assert_eq!(
+            claude.variants[0].args,
+            vec!["--dangerously-skip-permissions".to_string()]
+        );
+        assert!(!claude.variants[0].replace_args);
+        assert!(claude.variants[1].replace_args);
+        assert_eq!(
+            claude.variants[1]
+                .env
+                .get("MAX_THINKING")
+                .map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(
+            claude.display_name(claude.find_variant("YOLO")),
+            "claude › yolo"
+        );
+
+        save_agents(path, &loaded).unwrap();
+        assert_eq!(load_agents(path).unwrap(), loaded);
+    }
+
+    #[test]
+    fn test_load_agent_resume_args() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+        let kdl = r#"
+agent name="codex" {
+    cmd "codex"
+    resume "resume" "--last"
+}
+"#;
+        std::fs::write(path, kdl).unwrap();
+
+        let loaded = load_agents(path).unwrap();
+        assert_eq!(loaded[0].resume_args, vec!["resume", "--last"]);
+        assert!(loaded[0].args.is_empty());
+
+        save_agents(path, &loaded).unwrap();
+        assert_eq!(load_agents(path).unwrap(), loaded);
+    }
+
+    #[test]
+    fn test_load_agent_placement() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+        let kdl = r#"
+agent name="claude" {
+    cmd "claude"
+    placement "floating" width="80%" height=30
+}
+agent name="codex" {
+    cmd "codex"
+    placement "stacked"
+}
+"#;
+        std::fs::write(path, kdl).unwrap();
+
+        let loaded = load_agents(path).unwrap();
+        let floating = loaded[0].placement.as_ref().unwrap();
+        assert_eq!(floating.kind, PlacementKind::Floating);
+        assert_eq!(floating.width.as_deref(), Some("80%"));
+        assert_eq!(floating.height.as_deref(), Some("30"));
+        assert_eq!(
+            loaded[1].placement,
+            Some(Placement::new(PlacementKind::Stacked))
+        );
+
+        save_agents(path, &loaded).unwrap();
+        assert_eq!(load_agents(path).unwrap(), loaded);
+
+        std::fs::write(
+            path,
+            "agent name=\"a\" {\n cmd \"a\"\n placement \"sideways\"\n}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_agents(path),
+            Err(MaestroError::InvalidPlacement(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_agent_layout() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path();
+        let kdl = r#"
+agent name="claude" {
+    cmd "claude"
+    layout {
+        pane split_direction="vertical" {
+            agent
+            pane command="cargo" {
+                args "watch"
+            }
+        }
+    }
+}
+"#;
+        std::fs::write(path, kdl).unwrap();
+
+        let loaded = load_agents(path).unwrap();
+        let layout = loaded[0].layout.as_deref().unwrap();
+        assert!(layout.starts_with("pane split_direction=\"vertical\""));
+        assert!(layout.contains("agent"));
+
+        save_agents(path, &loaded).unwrap();
+        assert_eq!(load_agents(path).unwrap(), loaded);
+
+        std::fs::write(
+            path,
+            "agent name=\"a\" {\n cmd \"a\"\n layout {\n pane\n }\n}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_agents(path),
+            Err(MaestroError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_agents_duplicate_variant() {
+        let mut agent = crate::test_helpers::create_test_agent("claude");
+        agent.variants = vec![
+            AgentVariant {
+                name: "yolo".to_string(),
+                ..Default::default()
+            },
+            AgentVariant {
+                name: "Yolo".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert!(matches!(
+            validate_agents(&[agent]),
+            Err(MaestroError::InvalidAgentConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_variant_name() {
+        assert_eq!(split_variant_name("claude"), ("claude", None));
+        assert_eq!(
+            split_variant_name("claude › yolo"),
+            ("claude", Some("yolo"))
+        );
+    }
 }
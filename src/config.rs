@@ -0,0 +1,226 @@
+//! Plugin configuration read from the Zellij plugin block.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::keymap::Keymap;
+use crate::placement::{Placement, PlacementKind};
+use crate::projects::DEFAULT_PROJECT_DEPTH;
+
+/// Settings passed to the plugin through its Zellij configuration block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Whether the built-in default agents are merged into the agent list.
+    pub default_agents: bool,
+    /// Placement of new agent panes unless the agent or wizard overrides it.
+    pub placement: Placement,
+    /// How the new-pane wizard picks the tab for a new pane.
+    pub tab_selection: TabSelection,
+    /// Directories (relative to the plugin `cwd`) scanned for git repositories.
+    pub project_roots: Vec<String>,
+    /// How many levels below each project root repositories are looked for.
+    pub project_depth: usize,
+    /// Host home directory `~` expands to. Plugins run without `$HOME`, so
+    /// `~` is rejected until this is set.
+    pub home: Option<PathBuf>,
+    /// Keys bound to each action, from the `key_<action>` entries.
+    pub keymap: Keymap,
+    /// Problems found in the configuration, shown on the error line once
+    /// the plugin has loaded.
+    pub errors: Vec<String>,
+}
+
+/// Tab a new agent pane opens in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TabSelection {
+    /// A tab named after the workspace, created when missing.
+    #[default]
+    Workspace,
+    /// The currently active tab.
+    Current,
+    /// Ask in the wizard: the current tab, any existing tab or a new named tab.
+    Ask,
+}
+
+impl TabSelection {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "workspace" => Some(Self::Workspace),
+            "current" => Some(Self::Current),
+            "ask" => Some(Self::Ask),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_agents: true,
+            placement: Placement::default(),
+            tab_selection: TabSelection::default(),
+            project_roots: Vec::new(),
+            project_depth: DEFAULT_PROJECT_DEPTH,
+            home: None,
+            keymap: Keymap::default(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Build a config from the plugin configuration map, falling back to
+    /// defaults for missing or unparseable values.
+    pub fn from_map(map: &BTreeMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let (keymap, key_conflicts) = Keymap::from_map(map);
+        Self {
+            default_agents: map
+                .get("default_agents")
+                .and_then(|v| parse_bool(v))
+                .unwrap_or(defaults.default_agents),
+            placement: parse_placement(map).unwrap_or(defaults.placement),
+            tab_selection: map
+                .get("tab_selection")
+                .and_then(|v| TabSelection::parse(v))
+                .unwrap_or(defaults.tab_selection),
+            project_roots: map
+                .get("project_roots")
+                .map(|v| parse_list(v))
+                .unwrap_or(defaults.project_roots),
+            project_depth: map
+                .get("project_depth")
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.project_depth),
+            home: map
+                .get("home")
+                .map(|v| v.trim())
+                .filter(|v| v.starts_with('/'))
+                .map(PathBuf::from),
+            keymap,
+            errors: key_conflicts.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// Read `placement` and its `placement_x`/`_y`/`_width`/`_height` geometry.
+fn parse_placement(map: &BTreeMap<String, String>) -> Option<Placement> {
+    let kind = PlacementKind::parse(map.get("placement")?).ok()?;
+    let geometry = |key: &str| map.get(&format!("placement_{key}")).cloned();
+    let placement = Placement {
+        kind,
+        x: geometry("x"),
+        y: geometry("y"),
+        width: geometry("width"),
+        height: geometry("height"),
+    };
+    placement.validate().ok()?;
+    Some(placement)
+}
+
+/// Split a comma-separated list, dropping empty items.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::Action;
+
+    fn map(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = Config::from_map(&BTreeMap::new());
+        assert_eq!(config, Config::default());
+        assert!(config.default_agents);
+    }
+
+    #[test]
+    fn test_config_default_agents_disabled() {
+        let config = Config::from_map(&map(&[("default_agents", "false")]));
+        assert!(!config.default_agents);
+    }
+
+    #[test]
+    fn test_config_placement() {
+        let config = Config::from_map(&map(&[
+            ("placement", "floating"),
+            ("placement_width", "60%"),
+        ]));
+        assert_eq!(config.placement.kind, PlacementKind::Floating);
+        assert_eq!(config.placement.width.as_deref(), Some("60%"));
+
+        let config = Config::from_map(&map(&[("placement", "diagonal")]));
+        assert_eq!(config.placement, Placement::default());
+    }
+
+    #[test]
+    fn test_config_tab_selection() {
+        let config = Config::from_map(&map(&[("tab_selection", "Ask")]));
+        assert_eq!(config.tab_selection, TabSelection::Ask);
+
+        let config = Config::from_map(&map(&[("tab_selection", "elsewhere")]));
+        assert_eq!(config.tab_selection, TabSelection::Workspace);
+    }
+
+    #[test]
+    fn test_config_project_roots() {
+        let config = Config::from_map(&map(&[
+            ("project_roots", "src, work ,,"),
+            ("project_depth", "2"),
+        ]));
+        assert_eq!(config.project_roots, vec!["src", "work"]);
+        assert_eq!(config.project_depth, 2);
+
+        let config = Config::from_map(&map(&[("project_depth", "deep")]));
+        assert_eq!(config.project_depth, DEFAULT_PROJECT_DEPTH);
+    }
+
+    #[test]
+    fn test_config_home() {
+        let config = Config::from_map(&map(&[("home", " /home/me ")]));
+        assert_eq!(config.home, Some(PathBuf::from("/home/me")));
+
+        let config = Config::from_map(&map(&[("home", "~")]));
+        assert_eq!(config.home, None);
+    }
+
+    #[test]
+    fn test_config_keymap() {
+        let config = Config::from_map(&map(&[("key_new_pane", "Ctrl o")]));
+        assert_eq!(config.keymap.keys(Action::NewPane).len(), 1);
+        assert_ne!(config.keymap, Keymap::default());
+        assert!(config.errors.is_empty());
+
+        // Key conflicts are kept for the error line.
+        let config = Config::from_map(&map(&[("key_new_pane", "d")]));
+        assert_eq!(config.keymap, Keymap::default());
+        assert_eq!(config.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_config_invalid_bool_falls_back() {
+        let config = Config::from_map(&map(&[("default_agents", "maybe")]));
+        assert!(config.default_agents);
+    }
+}
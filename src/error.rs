@@ -53,6 +53,59 @@ pub enum MaestroError {
     #[error("Invalid agent arguments: {0}")]
     InvalidAgentArgs(String),
 
+    #[error("Unknown placeholder: {0}")]
+    UnknownPlaceholder(String),
+
+    #[error("Invalid placement: {0}")]
+    InvalidPlacement(String),
+
+    #[error(
+        "Key {key} is bound to both {first} and {second} on the {screen} screen; \
+         {kept} keeps its default keys"
+    )]
+    KeyConflict {
+        key: String,
+        first: String,
+        second: String,
+        screen: String,
+        kept: String,
+    },
+
+    #[error("Invalid layout: {0}")]
+    InvalidLayout(String),
+
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Value required: {0}")]
+    ValueRequired(String),
+
+    // Import/export errors
+    #[error("Path required")]
+    PathRequired,
+
+    #[error("No agents found in {}", .0.display())]
+    NoAgentsFound(PathBuf),
+
+    #[error("No agents to export")]
+    NoAgentsToExport,
+
+    #[error("{} already exists", .0.display())]
+    ExportTargetExists(PathBuf),
+
+    // Workspace errors
+    #[error("{path} is outside what Maestro can see ({root})")]
+    OutsideSandbox { path: String, root: String },
+
+    #[error("Cannot expand ~: set `home` in the plugin configuration")]
+    HomeNotSet,
+
+    #[error("Workspace not found: {0}")]
+    WorkspaceNotFound(String),
+
+    #[error("Workspace is not a directory: {0}")]
+    WorkspaceNotDirectory(String),
+
     // Runtime errors
     #[error("Invalid mode")]
     InvalidMode,
@@ -65,6 +118,24 @@ pub enum MaestroError {
 
     #[error("Pane ID not available yet")]
     PaneIdUnavailable,
+
+    #[error("Command not found: {0}")]
+    AgentCommandMissing(String),
+
+    #[error("Only floating panes can be pinned")]
+    PaneNotFloating,
+
+    #[error("Agent pane is still running")]
+    PaneStillRunning,
+
+    #[error("No resume arguments configured for {0}")]
+    ResumeNotConfigured(String),
+
+    #[error("{0} exited with status {1}")]
+    TrialFailed(String, i32),
+
+    #[error("{0} exited without a status")]
+    TrialInterrupted(String),
 }
 
 /// Result type for user-facing Maestro operations.
@@ -160,6 +231,63 @@ mod tests {
             MaestroError::InvalidAgentArgs("unmatched quote".to_string()).to_string(),
             "Invalid agent arguments: unmatched quote"
         );
+        assert_eq!(
+            MaestroError::UnknownPlaceholder("{foo}".to_string()).to_string(),
+            "Unknown placeholder: {foo}"
+        );
+        assert_eq!(
+            MaestroError::InvalidPlacement("sideways".to_string()).to_string(),
+            "Invalid placement: sideways"
+        );
+        assert_eq!(
+            MaestroError::InvalidLayout("no `agent` pane".to_string()).to_string(),
+            "Invalid layout: no `agent` pane"
+        );
+        assert_eq!(
+            MaestroError::InvalidTemplate("unterminated '{'".to_string()).to_string(),
+            "Invalid template: unterminated '{'"
+        );
+        assert_eq!(
+            MaestroError::ValueRequired("model".to_string()).to_string(),
+            "Value required: model"
+        );
+    }
+
+    #[test]
+    fn test_error_display_import_export() {
+        assert_eq!(MaestroError::PathRequired.to_string(), "Path required");
+        assert_eq!(
+            MaestroError::NoAgentsFound(PathBuf::from("/host/agents.json")).to_string(),
+            "No agents found in /host/agents.json"
+        );
+        assert_eq!(
+            MaestroError::NoAgentsToExport.to_string(),
+            "No agents to export"
+        );
+        assert_eq!(
+            MaestroError::ExportTargetExists(PathBuf::from("/host/agents.kdl")).to_string(),
+            "/host/agents.kdl already exists"
+        );
+    }
+
+    #[test]
+    fn test_error_display_workspace() {
+        assert_eq!(
+            MaestroError::OutsideSandbox {
+                path: "/opt/work".to_string(),
+                root: "/home/me".to_string(),
+            }
+            .to_string(),
+            "/opt/work is outside what Maestro can see (/home/me)"
+        );
+        assert_eq!(
+            MaestroError::WorkspaceNotFound("src/app".to_string()).to_string(),
+            "Workspace not found: src/app"
+        );
+        assert_eq!(
+            MaestroError::WorkspaceNotDirectory("notes.txt".to_string()).to_string(),
+            "Workspace is not a directory: notes.txt"
+        );
     }
 
     #[test]
@@ -174,5 +302,29 @@ mod tests {
             MaestroError::PaneIdUnavailable.to_string(),
             "Pane ID not available yet"
         );
+        assert_eq!(
+            MaestroError::AgentCommandMissing("cursor-agent".to_string()).to_string(),
+            "Command not found: cursor-agent"
+        );
+        assert_eq!(
+            MaestroError::PaneNotFloating.to_string(),
+            "Only floating panes can be pinned"
+        );
+        assert_eq!(
+            MaestroError::PaneStillRunning.to_string(),
+            "Agent pane is still running"
+        );
+        assert_eq!(
+            MaestroError::ResumeNotConfigured("gemini".to_string()).to_string(),
+            "No resume arguments configured for gemini"
+        );
+        assert_eq!(
+            MaestroError::TrialFailed("codex".to_string(), 127).to_string(),
+            "codex exited with status 127"
+        );
+        assert_eq!(
+            MaestroError::TrialInterrupted("codex".to_string()).to_string(),
+            "codex exited without a status"
+        );
     }
 }
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use zellij_tile::prelude::*;
+
+use super::context::CTX_ACTION;
+use super::listing::{record_dir_listing, record_git_branch, ACTION_GIT_BRANCH, ACTION_LIST_DIR};
+use crate::agent::Availability;
+use crate::model::Model;
+
+const CTX_COMMAND: &str = "command";
+const ACTION_AVAILABILITY: &str = "availability";
+
+/// Start background `command -v` checks for agent commands not yet known.
+/// With `force`, previously recorded results are discarded and re-checked.
+pub fn request_availability_checks(model: &mut Model, force: bool) {
+    if !model.permissions_granted {
+        return;
+    }
+    if force {
+        model.command_availability.clear();
+    }
+    for command in pending_commands(model) {
+        start_check(model, command);
+    }
+}
+
+/// Check a single command, such as one typed in the agent form, unless its
+/// availability is already known or being checked.
+pub fn request_command_check(model: &mut Model, command: &str) {
+    let command = command.trim();
+    if !model.permissions_granted
+        || command.is_empty()
+        || model.command_availability.contains_key(command)
+    {
+        return;
+    }
+    start_check(model, command.to_string());
+}
+
+fn start_check(model: &mut Model, command: String) {
+    let mut ctx = BTreeMap::new();
+    ctx.insert(CTX_ACTION.to_string(), ACTION_AVAILABILITY.to_string());
+    ctx.insert(CTX_COMMAND.to_string(), command.clone());
+    run_command(&["sh", "-c", "command -v \"$1\"", "sh", &command], ctx);
+    model
+        .command_availability
+        .insert(command, Availability::Unknown);
+}
+
+/// Route a finished background command back to the check that started it.
+pub fn handle_run_command_result(
+    model: &mut Model,
+    exit_code: Option<i32>,
+    stdout: &[u8],
+    ctx: BTreeMap<String, String>,
+) {
+    match ctx.get(CTX_ACTION).map(String::as_str) {
+        Some(ACTION_AVAILABILITY) => {
+            if let Some(command) = ctx.get(CTX_COMMAND) {
+                record_availability(model, command, exit_code);
+            }
+        }
+        Some(ACTION_LIST_DIR) => record_dir_listing(model, exit_code, stdout, &ctx),
+        Some(ACTION_GIT_BRANCH) => record_git_branch(model, exit_code, stdout, &ctx),
+        _ => {}
+    }
+}
+
+fn pending_commands(model: &Model) -> Vec<String> {
+    let mut commands: Vec<String> = model
+        .agents
+        .iter()
+        .map(|a| a.command.trim().to_string())
+        .filter(|c| !c.is_empty() && !model.command_availability.contains_key(c))
+        .collect();
+    commands.sort();
+    commands.dedup();
+    commands
+}
+
+fn record_availability(model: &mut Model, command: &str, exit_code: Option<i32>) {
+    let availability = if exit_code == Some(0) {
+        Availability::Available
+    } else {
+        Availability::Missing
+    };
+    model
+        .command_availability
+        .insert(command.to_string(), availability);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_agent;
+
+    fn availability_ctx(command: &str) -> BTreeMap<String, String> {
+        let mut ctx = BTreeMap::new();
+        ctx.insert(CTX_ACTION.to_string(), ACTION_AVAILABILITY.to_string());
+        ctx.insert(CTX_COMMAND.to_string(), command.to_string());
+        ctx
+    }
+
+    #[test]
+    fn pending_commands_skips_known_and_duplicates() {
+        let mut model = Model::default();
+        model.agents.push(create_test_agent("one"));
+        model.agents.push(create_test_agent("two"));
+        let mut other = create_test_agent("three");
+        other.command = "claude".to_string();
+        model.agents.push(other);
+
+        assert_eq!(pending_commands(&model), vec!["claude", "echo"]);
+
+        model
+            .command_availability
+            .insert("echo".to_string(), Availability::Available);
+        assert_eq!(pending_commands(&model), vec!["claude"]);
+    }
+
+    #[test]
+    fn run_command_result_marks_availability() {
+        let mut model = Model::default();
+        let agent = create_test_agent("one");
+        model.agents.push(agent.clone());
+
+        handle_run_command_result(&mut model, Some(0), b"", availability_ctx("echo"));
+        assert_eq!(model.agent_availability(&agent), Availability::Available);
+
+        handle_run_command_result(&mut model, Some(1), b"", availability_ctx("echo"));
+        assert_eq!(model.agent_availability(&agent), Availability::Missing);
+    }
+
+    #[test]
+    fn run_command_result_ignores_other_contexts() {
+        let mut model = Model::default();
+        handle_run_command_result(&mut model, Some(1), b"", BTreeMap::new());
+        assert!(model.command_availability.is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+//! Context keys shared by the handlers that start background commands.
+
+/// Context key identifying which background command a `RunCommandResult` answers.
+pub(super) const CTX_ACTION: &str = "maestro_action";
@@ -1,21 +1,38 @@
-use zellij_tile::prelude::{BareKey, KeyWithModifier};
+use std::path::PathBuf;
 
-use crate::agent::{default_config_path, names_match, save_agents, Agent};
+use zellij_tile::prelude::KeyWithModifier;
+
+use crate::agent::{default_config_path, names_match, save_agents, unique_agent_name, Agent};
 use crate::error::{MaestroError, MaestroResult};
+use crate::input::TextInput;
 use crate::model::Model;
+use crate::projects::scan_projects;
+use crate::share::{merge_agents, read_agents_file, write_agents_file};
+use crate::template::{validate_templates, PromptVar};
 use crate::ui::{AgentFormField, Mode};
+use crate::utils::{create_workspace, host_file_path, host_fs_path, validate_workspace};
 
-pub(super) fn handle_text_edit(target: &mut String, key: &KeyWithModifier) -> bool {
-    match key.bare_key {
-        BareKey::Backspace => {
-            target.pop();
-            true
-        }
-        BareKey::Char(c) => {
-            target.push(c);
-            true
+const DEFAULT_EXPORT_PATH: &str = "maestro-agents.kdl";
+
+pub(super) fn handle_text_edit(target: &mut TextInput, key: &KeyWithModifier) -> bool {
+    target.handle_key(key)
+}
+
+/// Insert pasted text into the focused input, as if it had been typed.
+pub fn handle_paste(model: &mut Model, text: &str) {
+    let Some(input) = model.active_input_mut() else {
+        return;
+    };
+    input.insert_str(text);
+    match model.mode {
+        Mode::NewPaneWorkspace => {
+            model.pane_wizard.browse_idx = 0;
+            model.pane_wizard.create_workspace = None;
+            model.clear_error();
+            model.refresh_workspace_suggestions();
         }
-        _ => false,
+        Mode::NewPaneTabSelect => model.pane_wizard.tab_idx = model.tab_names.len() + 1,
+        _ => {}
     }
 }
 
@@ -24,11 +41,181 @@ pub(super) fn handle_form_text(model: &mut Model, key: &KeyWithModifier) -> bool
 }
 
 pub(super) fn start_new_pane_workspace(model: &mut Model) {
+    if model.projects.is_none() {
+        refresh_projects(model);
+    }
     model.pane_wizard.clear();
+    model.refresh_workspace_suggestions();
     model.mode = Mode::NewPaneWorkspace;
     model.clear_error();
 }
 
+/// Enter the tab step, highlighting the tab named after the workspace when it
+/// exists and otherwise offering that name for a new tab.
+pub(super) fn start_new_pane_tab_select(model: &mut Model, default_tab: String) {
+    let wizard = &mut model.pane_wizard;
+    match model.tab_names.iter().position(|t| *t == default_tab) {
+        Some(idx) => {
+            wizard.tab_idx = idx + 1;
+            wizard.tab_input.clear();
+        }
+        None => {
+            wizard.tab_idx = model.tab_names.len() + 1;
+            wizard.tab_input.set(default_tab);
+        }
+    }
+    model.mode = Mode::NewPaneTabSelect;
+    model.clear_error();
+}
+
+/// Record the highlighted entry of the tab step in the wizard.
+pub(super) fn apply_tab_select(model: &mut Model) -> MaestroResult<()> {
+    let wizard = &mut model.pane_wizard;
+    wizard.use_current_tab = wizard.tab_idx == 0;
+    wizard.tab_name = match wizard.tab_idx {
+        0 => None,
+        idx if idx <= model.tab_names.len() => Some(model.tab_names[idx - 1].clone()),
+        _ => {
+            let name = wizard.tab_input.trim();
+            if name.is_empty() {
+                return Err(MaestroError::ValueRequired("tab name".to_string()));
+            }
+            Some(name.to_string())
+        }
+    };
+    Ok(())
+}
+
+/// Check the chosen workspace before moving on. A missing workspace is
+/// offered for creation and created when it is submitted a second time.
+/// An accepted workspace has its git branch looked up in the background.
+pub(super) fn confirm_workspace(model: &mut Model) -> MaestroResult<()> {
+    let workspace = model.pane_wizard.workspace.trim().to_string();
+    let result = check_workspace(model, &workspace);
+    if result.is_ok() {
+        model.branches.request(&host_fs_path(&workspace));
+    }
+    result
+}
+
+fn check_workspace(model: &mut Model, workspace: &str) -> MaestroResult<()> {
+    let workspace = workspace.to_string();
+    match validate_workspace(&workspace) {
+        Err(MaestroError::WorkspaceNotFound(_))
+            if model.pane_wizard.create_workspace.as_deref() == Some(workspace.as_str()) =>
+        {
+            create_workspace(&workspace)?;
+            if let Some(parent) = host_fs_path(&workspace).parent() {
+                model.dir_cache.invalidate(parent);
+            }
+            model.pane_wizard.create_workspace = None;
+            model.notice = format!("Created {workspace}");
+            Ok(())
+        }
+        Err(err @ MaestroError::WorkspaceNotFound(_)) => {
+            model.pane_wizard.create_workspace = Some(workspace);
+            Err(err)
+        }
+        other => {
+            model.pane_wizard.create_workspace = None;
+            other
+        }
+    }
+}
+
+/// Rescan the configured project roots for repositories.
+pub(super) fn refresh_projects(model: &mut Model) {
+    let roots: Vec<PathBuf> = model
+        .config
+        .project_roots
+        .iter()
+        .map(|root| host_fs_path(root))
+        .collect();
+    model.projects = Some(scan_projects(&roots, model.config.project_depth));
+}
+
+/// Forget the highlighted recent workspace while the wizard input is empty.
+pub(super) fn remove_recent_workspace(model: &mut Model) {
+    if !model.pane_wizard.workspace.trim().is_empty() {
+        return;
+    }
+    let Some(path) = model
+        .pane_wizard
+        .suggestions
+        .get(model.pane_wizard.browse_idx)
+        .map(|s| s.path.clone())
+    else {
+        return;
+    };
+    if model.workspace_history.remove(&path) {
+        model.notice = format!("Removed {path} from recent workspaces");
+    }
+    model.refresh_workspace_suggestions();
+    let remaining = model.pane_wizard.suggestions.len();
+    if model.pane_wizard.browse_idx >= remaining {
+        model.pane_wizard.browse_idx = remaining.saturating_sub(1);
+    }
+}
+
+pub(super) fn start_new_pane_prompts(model: &mut Model, prompts: Vec<PromptVar>) {
+    model.pane_wizard.prompt_input.set(
+        prompts
+            .first()
+            .and_then(|p| p.default.clone())
+            .unwrap_or_default(),
+    );
+    model.pane_wizard.prompts = prompts;
+    model.pane_wizard.prompt_idx = 0;
+    model.pane_wizard.prompt_values.clear();
+    model.mode = Mode::NewPanePrompt;
+    model.clear_error();
+}
+
+/// Replace the prompt input with the next choice of the current variable.
+pub(super) fn cycle_prompt_choice(model: &mut Model) {
+    let wizard = &mut model.pane_wizard;
+    let Some(prompt) = wizard.prompts.get(wizard.prompt_idx) else {
+        return;
+    };
+    if prompt.choices.is_empty() {
+        return;
+    }
+    let next = prompt
+        .choices
+        .iter()
+        .position(|c| c == wizard.prompt_input.trim())
+        .map(|i| (i + 1) % prompt.choices.len())
+        .unwrap_or(0);
+    wizard.prompt_input.set(prompt.choices[next].clone());
+}
+
+/// Record the value for the current prompt and advance.
+/// Returns `Ok(true)` once every prompt has been answered.
+pub(super) fn submit_prompt_value(model: &mut Model) -> MaestroResult<bool> {
+    let wizard = &mut model.pane_wizard;
+    let Some(prompt) = wizard.prompts.get(wizard.prompt_idx) else {
+        return Ok(true);
+    };
+    let input = wizard.prompt_input.trim();
+    let value = if input.is_empty() {
+        prompt
+            .default
+            .clone()
+            .ok_or_else(|| MaestroError::ValueRequired(prompt.name.clone()))?
+    } else {
+        input.to_string()
+    };
+    wizard.prompt_values.insert(prompt.name.clone(), value);
+    wizard.prompt_idx += 1;
+    let default = wizard
+        .prompts
+        .get(wizard.prompt_idx)
+        .and_then(|p| p.default.clone())
+        .unwrap_or_default();
+    wizard.prompt_input.set(default);
+    Ok(wizard.prompt_idx >= wizard.prompts.len())
+}
+
 pub(super) fn start_agent_create(model: &mut Model) {
     model.agent_form.clear();
     model.mode = Mode::AgentFormCreate;
@@ -44,10 +231,13 @@ pub(super) fn start_agent_edit(model: &mut Model) {
         .selected_agent
         .min(model.agents.len().saturating_sub(1));
     if let Some(agent) = model.agents.get(idx) {
-        model.agent_form.name = agent.name.clone();
-        model.agent_form.command = agent.command.clone();
-        model.agent_form.args = shell_words::join(&agent.args);
-        model.agent_form.note = agent.note.clone().unwrap_or_default();
+        model.agent_form.name.set(agent.name.clone());
+        model.agent_form.command.set(agent.command.clone());
+        model.agent_form.args.set(shell_words::join(&agent.args));
+        model
+            .agent_form
+            .note
+            .set(agent.note.clone().unwrap_or_default());
         model.agent_form.field = AgentFormField::Name;
         model.agent_form.target = Some(idx);
         model.mode = Mode::AgentFormEdit;
@@ -55,6 +245,43 @@ pub(super) fn start_agent_edit(model: &mut Model) {
     }
 }
 
+/// Open the create form pre-filled from the selected agent, under a name
+/// no other agent uses.
+pub(super) fn start_agent_clone(model: &mut Model) {
+    let Some(agent) = model.agents.get(model.selected_agent) else {
+        model.error_message = MaestroError::NoAgentSelected.to_string();
+        return;
+    };
+    let name = unique_agent_name(&model.agents, &agent.name);
+    let form = &mut model.agent_form;
+    form.clear();
+    form.name.set(name);
+    form.command.set(agent.command.clone());
+    form.args.set(shell_words::join(&agent.args));
+    form.note.set(agent.note.clone().unwrap_or_default());
+    form.clone_of = Some(agent.name.clone());
+    model.mode = Mode::AgentFormCreate;
+    model.clear_error();
+}
+
+/// Move the selected agent `delta` places up or down the list and save the
+/// new order.
+pub(super) fn move_selected_agent(model: &mut Model, delta: isize) -> MaestroResult<()> {
+    let idx = model.selected_agent;
+    if idx >= model.agents.len() {
+        return Err(MaestroError::NoAgentSelected);
+    }
+    let Some(target) = idx
+        .checked_add_signed(delta)
+        .filter(|t| *t < model.agents.len())
+    else {
+        return Ok(());
+    };
+    model.agents.swap(idx, target);
+    let name = model.agents[target].name.clone();
+    persist_agents(model, Some(&name))
+}
+
 pub(super) fn start_agent_delete_confirm(model: &mut Model) {
     if model.agents.is_empty() {
         model.error_message = MaestroError::NoAgentsToDelete.to_string();
@@ -68,6 +295,87 @@ pub(super) fn start_agent_delete_confirm(model: &mut Model) {
     model.clear_error();
 }
 
+pub(super) fn start_agent_import(model: &mut Model) {
+    model.agent_transfer.clear();
+    model.mode = Mode::AgentImport;
+    model.clear_error();
+}
+
+pub(super) fn start_agent_export(model: &mut Model) {
+    if model.agents.is_empty() {
+        model.error_message = MaestroError::NoAgentsToExport.to_string();
+        return;
+    }
+    model.agent_transfer.clear();
+    model.agent_transfer.path.set(DEFAULT_EXPORT_PATH);
+    model.mode = Mode::AgentExport;
+    model.clear_error();
+}
+
+pub(super) fn toggle_agent_mark(model: &mut Model) {
+    if let Some(agent) = model.agents.get(model.selected_agent) {
+        let key = agent.name.to_lowercase();
+        if !model.marked_agents.remove(&key) {
+            model.marked_agents.insert(key);
+        }
+    }
+}
+
+/// Import agents from the prompted path and persist the merged list.
+/// Returns a summary for the status line.
+pub(super) fn apply_agent_import(model: &mut Model) -> MaestroResult<String> {
+    let input = model.agent_transfer.path.trim();
+    if input.is_empty() {
+        return Err(MaestroError::PathRequired);
+    }
+    let incoming = read_agents_file(&host_file_path(input, &model.path_context()))?;
+    let summary = merge_agents(&mut model.agents, incoming, model.agent_transfer.strategy)?;
+    persist_agents(model, None)?;
+    model.agent_transfer.clear();
+    Ok(summary.describe())
+}
+
+/// Export the marked agents (or the selected one if none are marked) to the
+/// prompted path. An existing file is only overwritten when the same path is
+/// submitted a second time. Returns a summary for the status line.
+pub(super) fn apply_agent_export(model: &mut Model) -> MaestroResult<String> {
+    let input = model.agent_transfer.path.trim();
+    if input.is_empty() {
+        return Err(MaestroError::PathRequired);
+    }
+    let selected: Vec<Agent> = if model.marked_agents.is_empty() {
+        model
+            .agents
+            .get(model.selected_agent)
+            .cloned()
+            .into_iter()
+            .collect()
+    } else {
+        model
+            .agents
+            .iter()
+            .filter(|a| model.marked_agents.contains(&a.name.to_lowercase()))
+            .cloned()
+            .collect()
+    };
+    if selected.is_empty() {
+        return Err(MaestroError::NoAgentsToExport);
+    }
+    let path = host_file_path(input, &model.path_context());
+    if path.exists() && model.agent_transfer.overwrite.as_ref() != Some(&path) {
+        model.agent_transfer.overwrite = Some(path.clone());
+        return Err(MaestroError::ExportTargetExists(path));
+    }
+    write_agents_file(&path, &selected)?;
+    model.marked_agents.clear();
+    model.agent_transfer.clear();
+    Ok(format!(
+        "Exported {} agent(s) to {}",
+        selected.len(),
+        path.display()
+    ))
+}
+
 pub(super) fn build_agent_from_inputs(model: &Model) -> MaestroResult<Agent> {
     let name = model.agent_form.name.trim().to_string();
     if name.is_empty() {
@@ -79,6 +387,7 @@ pub(super) fn build_agent_from_inputs(model: &Model) -> MaestroResult<Agent> {
     }
     let args = shell_words::split(&model.agent_form.args)
         .map_err(|e| MaestroError::InvalidAgentArgs(e.to_string()))?;
+    validate_templates(std::iter::once(&command).chain(&args))?;
     let note = if model.agent_form.note.trim().is_empty() {
         None
     } else {
@@ -89,9 +398,20 @@ pub(super) fn build_agent_from_inputs(model: &Model) -> MaestroResult<Agent> {
         command,
         args,
         note,
+        ..Default::default()
     })
 }
 
+pub(super) fn toggle_agent_hidden(model: &mut Model) -> MaestroResult<()> {
+    let agent = model
+        .agents
+        .get_mut(model.selected_agent)
+        .ok_or(MaestroError::NoAgentSelected)?;
+    agent.hidden = !agent.hidden;
+    let name = agent.name.clone();
+    persist_agents(model, Some(&name))
+}
+
 fn set_selection_by_name(model: &mut Model, name: &str) {
     if let Some(pos) = model.agents.iter().position(|a| names_match(&a.name, name)) {
         model.selected_agent = pos;
@@ -100,7 +420,7 @@ fn set_selection_by_name(model: &mut Model, name: &str) {
     }
 }
 
-pub(super) fn apply_agent_create(model: &mut Model, agent: Agent) -> MaestroResult<()> {
+pub(super) fn apply_agent_create(model: &mut Model, mut agent: Agent) -> MaestroResult<()> {
     if model
         .agents
         .iter()
@@ -109,11 +429,28 @@ pub(super) fn apply_agent_create(model: &mut Model, agent: Agent) -> MaestroResu
         return Err(MaestroError::DuplicateAgentName(agent.name.clone()));
     }
     let name = agent.name.clone();
-    model.agents.push(agent);
+    // A clone keeps what the form does not show and goes right after its original.
+    let original = model.agent_form.clone_of.as_deref().and_then(|source| {
+        model
+            .agents
+            .iter()
+            .position(|a| names_match(&a.name, source))
+    });
+    match original {
+        Some(idx) => {
+            let original = &model.agents[idx];
+            agent.variants = original.variants.clone();
+            agent.resume_args = original.resume_args.clone();
+            agent.placement = original.placement.clone();
+            agent.layout = original.layout.clone();
+            model.agents.insert(idx + 1, agent);
+        }
+        None => model.agents.push(agent),
+    }
     persist_agents(model, Some(&name))
 }
 
-pub(super) fn apply_agent_edit(model: &mut Model, agent: Agent) -> MaestroResult<()> {
+pub(super) fn apply_agent_edit(model: &mut Model, mut agent: Agent) -> MaestroResult<()> {
     if let Some(idx) = model.agent_form.target {
         if idx < model.agents.len() {
             if model
@@ -124,6 +461,13 @@ pub(super) fn apply_agent_edit(model: &mut Model, agent: Agent) -> MaestroResult
             {
                 return Err(MaestroError::DuplicateAgentName(agent.name.clone()));
             }
+            // Visibility, variants, resume args, placement and layout are not
+            // part of the form; keep them.
+            agent.hidden = model.agents[idx].hidden;
+            agent.variants = model.agents[idx].variants.clone();
+            agent.resume_args = model.agents[idx].resume_args.clone();
+            agent.placement = model.agents[idx].placement.clone();
+            agent.layout = model.agents[idx].layout.clone();
             let name = agent.name.clone();
             model.agents[idx] = agent;
             return persist_agents(model, Some(&name));
@@ -140,9 +484,9 @@ pub(super) fn persist_agents(model: &mut Model, focus_name: Option<&str>) -> Mae
             message: e.to_string(),
         })?;
     }
-    // Persist full agent list so user customizations to built-in defaults are retained.
+    // Built-ins left as shipped are saved as markers, edited ones in full.
     save_agents(&path, &model.agents)?;
-    model.agents = crate::agent::load_agents_default()?;
+    model.agents = crate::agent::load_agents_default(&model.config)?;
     if let Some(name) = focus_name {
         set_selection_by_name(model, name);
     } else {
@@ -157,6 +501,7 @@ mod tests {
     use super::*;
     use crate::model::Model;
     use crate::test_helpers::create_test_agent;
+
     use zellij_tile::prelude::{BareKey, KeyWithModifier};
 
     fn create_test_model() -> Model {
@@ -179,26 +524,40 @@ mod tests {
 
     #[test]
     fn test_handle_text_edit_char() {
-        let mut target = String::new();
+        let mut target = TextInput::default();
         let key = char_key('a');
         assert!(handle_text_edit(&mut target, &key));
-        assert_eq!(target, "a");
+        assert_eq!(target.value(), "a");
     }
 
     #[test]
     fn test_handle_text_edit_backspace() {
-        let mut target = "hello".to_string();
+        let mut target = TextInput::from("hello");
         let key = backspace_key();
         assert!(handle_text_edit(&mut target, &key));
-        assert_eq!(target, "hell");
+        assert_eq!(target.value(), "hell");
     }
 
     #[test]
     fn test_handle_text_edit_backspace_empty() {
-        let mut target = String::new();
+        let mut target = TextInput::default();
         let key = backspace_key();
         assert!(handle_text_edit(&mut target, &key));
-        assert_eq!(target, "");
+        assert_eq!(target.value(), "");
+    }
+
+    #[test]
+    fn test_handle_paste_into_focused_input() {
+        let mut model = create_test_model();
+        model.mode = Mode::AgentFormEdit;
+        model.agent_form.field = AgentFormField::Args;
+        model.agent_form.args = "--model ".into();
+        handle_paste(&mut model, "opus\n");
+        assert_eq!(model.agent_form.args, "--model opus");
+
+        model.mode = Mode::View;
+        handle_paste(&mut model, "ignored");
+        assert_eq!(model.agent_form.args, "--model opus");
     }
 
     #[test]
@@ -222,10 +581,10 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_valid() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "echo".to_string();
-        model.agent_form.args = "hello world".to_string();
-        model.agent_form.note = "test note".to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "echo".into();
+        model.agent_form.args = "hello world".into();
+        model.agent_form.note = "test note".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_ok());
@@ -239,8 +598,8 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_empty_name() {
         let mut model = create_test_model();
-        model.agent_form.name = "   ".to_string();
-        model.agent_form.command = "echo".to_string();
+        model.agent_form.name = "   ".into();
+        model.agent_form.command = "echo".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_err());
@@ -253,8 +612,8 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_empty_command() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "   ".to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "   ".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_err());
@@ -264,9 +623,9 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_empty_note() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "echo".to_string();
-        model.agent_form.note = "   ".to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "echo".into();
+        model.agent_form.note = "   ".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_ok());
@@ -277,9 +636,9 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_with_args() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "codex".to_string();
-        model.agent_form.args = "/review --verbose".to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "codex".into();
+        model.agent_form.args = "/review --verbose".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_ok());
@@ -299,6 +658,7 @@ mod tests {
             command: "codex".to_string(),
             args: vec!["/review".to_string(), "hello world".to_string()],
             note: None,
+            ..Default::default()
         });
         model.selected_agent = 0;
 
@@ -310,9 +670,9 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_quoted_args() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "codex".to_string();
-        model.agent_form.args = r#"/review --prompt "hello world" file\ name"#.to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "codex".into();
+        model.agent_form.args = r#"/review --prompt "hello world" file\ name"#.into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_ok());
@@ -331,9 +691,9 @@ mod tests {
     #[test]
     fn test_build_agent_from_inputs_invalid_args() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "codex".to_string();
-        model.agent_form.args = "/review \"unterminated".to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "codex".into();
+        model.agent_form.args = "/review \"unterminated".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_err());
@@ -343,12 +703,27 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_build_agent_from_inputs_placeholders() {
+        let mut model = create_test_model();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "claude".into();
+        model.agent_form.args = "--model {?model=opus|sonnet} --dir {workspace}".into();
+        assert!(build_agent_from_inputs(&model).is_ok());
+
+        model.agent_form.args = "--dir {workspce}".into();
+        assert!(matches!(
+            build_agent_from_inputs(&model),
+            Err(MaestroError::UnknownPlaceholder(_))
+        ));
+    }
+
     #[test]
     fn test_build_agent_from_inputs_empty_args() {
         let mut model = create_test_model();
-        model.agent_form.name = "test-agent".to_string();
-        model.agent_form.command = "echo".to_string();
-        model.agent_form.args = "   ".to_string();
+        model.agent_form.name = "test-agent".into();
+        model.agent_form.command = "echo".into();
+        model.agent_form.args = "   ".into();
 
         let result = build_agent_from_inputs(&model);
         assert!(result.is_ok());
@@ -427,6 +802,235 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_toggle_agent_mark() {
+        let mut model = create_test_model();
+        model.agents.push(create_test_agent("Alpha"));
+        toggle_agent_mark(&mut model);
+        assert!(model.marked_agents.contains("alpha"));
+        toggle_agent_mark(&mut model);
+        assert!(model.marked_agents.is_empty());
+    }
+
+    #[test]
+    fn test_apply_agent_import_requires_path() {
+        let mut model = create_test_model();
+        model.agent_transfer.path = "  ".into();
+        assert!(matches!(
+            apply_agent_import(&mut model),
+            Err(MaestroError::PathRequired)
+        ));
+    }
+
+    #[test]
+    fn test_apply_agent_export_marked_agents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.json");
+        let mut model = create_test_model();
+        model.agents.push(create_test_agent("alpha"));
+        model.agents.push(create_test_agent("bravo"));
+        model.agents.push(create_test_agent("charlie"));
+        model.marked_agents.insert("alpha".to_string());
+        model.marked_agents.insert("charlie".to_string());
+        model.agent_transfer.path = path.to_string_lossy().into_owned().into();
+
+        let notice = apply_agent_export(&mut model).unwrap();
+        assert!(notice.starts_with("Exported 2 agent(s)"));
+        assert!(model.marked_agents.is_empty());
+
+        let exported = crate::share::read_agents_file(&path).unwrap();
+        let names: Vec<&str> = exported.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "charlie"]);
+    }
+
+    #[test]
+    fn test_apply_agent_export_confirms_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.kdl");
+        std::fs::write(&path, "keep me").unwrap();
+        let mut model = create_test_model();
+        model.agents.push(create_test_agent("alpha"));
+        model.agent_transfer.path = path.to_string_lossy().into_owned().into();
+
+        assert!(matches!(
+            apply_agent_export(&mut model),
+            Err(MaestroError::ExportTargetExists(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+
+        apply_agent_export(&mut model).unwrap();
+        let exported = crate::share::read_agents_file(&path).unwrap();
+        assert_eq!(exported[0].name, "alpha");
+    }
+
+    #[test]
+    fn test_start_agent_export_without_agents() {
+        let mut model = create_test_model();
+        model.agents.clear();
+        start_agent_export(&mut model);
+        assert_eq!(model.error_message, "No agents to export");
+        assert_ne!(model.mode, Mode::AgentExport);
+    }
+
+    #[test]
+    fn test_confirm_workspace_offers_creation() {
+        let mut model = Model::default();
+        model.pane_wizard.workspace =
+            format!("{}/no/such/workspace", crate::WASI_HOST_MOUNT).into();
+
+        assert!(matches!(
+            confirm_workspace(&mut model),
+            Err(MaestroError::WorkspaceNotFound(_))
+        ));
+        assert_eq!(
+            model.pane_wizard.create_workspace.as_deref(),
+            Some(model.pane_wizard.workspace.value())
+        );
+        assert!(model.branches.take_requests().is_empty());
+
+        model.pane_wizard.workspace = "/opt/outside".into();
+        assert!(confirm_workspace(&mut model).is_ok());
+        assert_eq!(model.pane_wizard.create_workspace, None);
+        // Accepted workspaces get their branch looked up.
+        assert_eq!(model.branches.take_requests().len(), 1);
+    }
+
+    #[test]
+    fn test_remove_recent_workspace() {
+        let mut model = Model::default();
+        model.workspace_history.record("/host/a", 1);
+        model.workspace_history.record("/host/b", 2);
+        model.refresh_workspace_suggestions();
+        model.pane_wizard.browse_idx = 1;
+
+        remove_recent_workspace(&mut model);
+        assert_eq!(model.workspace_history.visits().len(), 1);
+        assert_eq!(model.workspace_history.visits()[0].path, "/host/b");
+        assert_eq!(model.pane_wizard.browse_idx, 0);
+        assert!(model.notice.contains("/host/a"));
+
+        model.pane_wizard.workspace = "/host/b".into();
+        remove_recent_workspace(&mut model);
+        assert_eq!(model.workspace_history.visits().len(), 1);
+    }
+
+    #[test]
+    fn test_tab_select_flow() {
+        let mut model = Model {
+            tab_names: vec!["main".to_string(), "api".to_string()],
+            ..Default::default()
+        };
+
+        start_new_pane_tab_select(&mut model, "api".to_string());
+        assert_eq!(model.mode, Mode::NewPaneTabSelect);
+        assert_eq!(model.pane_wizard.tab_idx, 2);
+        apply_tab_select(&mut model).unwrap();
+        assert_eq!(model.pane_wizard.tab_name.as_deref(), Some("api"));
+        assert!(!model.pane_wizard.use_current_tab);
+
+        start_new_pane_tab_select(&mut model, "web".to_string());
+        assert_eq!(model.pane_wizard.tab_idx, 3);
+        assert_eq!(model.pane_wizard.tab_input, "web");
+        model.pane_wizard.tab_input = "  ".into();
+        assert!(apply_tab_select(&mut model).is_err());
+        model.pane_wizard.tab_input = "scratch".into();
+        apply_tab_select(&mut model).unwrap();
+        assert_eq!(model.pane_wizard.tab_name.as_deref(), Some("scratch"));
+
+        model.pane_wizard.tab_idx = 0;
+        apply_tab_select(&mut model).unwrap();
+        assert!(model.pane_wizard.use_current_tab);
+        assert_eq!(model.pane_wizard.tab_name, None);
+    }
+
+    #[test]
+    fn test_prompt_flow() {
+        let mut model = create_test_model();
+        start_new_pane_prompts(
+            &mut model,
+            vec![
+                PromptVar {
+                    name: "model".to_string(),
+                    default: Some("opus".to_string()),
+                    choices: vec!["opus".to_string(), "sonnet".to_string()],
+                },
+                PromptVar {
+                    name: "label".to_string(),
+                    default: None,
+                    choices: Vec::new(),
+                },
+            ],
+        );
+        assert_eq!(model.mode, Mode::NewPanePrompt);
+        assert_eq!(model.pane_wizard.prompt_input, "opus");
+
+        cycle_prompt_choice(&mut model);
+        assert_eq!(model.pane_wizard.prompt_input, "sonnet");
+        cycle_prompt_choice(&mut model);
+        assert_eq!(model.pane_wizard.prompt_input, "opus");
+        cycle_prompt_choice(&mut model);
+
+        assert!(!submit_prompt_value(&mut model).unwrap());
+        assert_eq!(model.pane_wizard.prompt_input, "");
+        assert!(matches!(
+            submit_prompt_value(&mut model),
+            Err(MaestroError::ValueRequired(name)) if name == "label"
+        ));
+
+        model.pane_wizard.prompt_input = "review".into();
+        assert!(submit_prompt_value(&mut model).unwrap());
+        assert_eq!(
+            model
+                .pane_wizard
+                .prompt_values
+                .get("model")
+                .map(String::as_str),
+            Some("sonnet")
+        );
+        assert_eq!(
+            model
+                .pane_wizard
+                .prompt_values
+                .get("label")
+                .map(String::as_str),
+            Some("review")
+        );
+    }
+
+    #[test]
+    fn test_start_agent_clone() {
+        let mut model = create_test_model();
+        let mut claude = create_test_agent("claude");
+        claude.args = vec!["--model".to_string(), "opus 4".to_string()];
+        model.agents.push(claude);
+        model.agents.push(create_test_agent("claude-2"));
+
+        start_agent_clone(&mut model);
+        assert_eq!(model.mode, Mode::AgentFormCreate);
+        assert_eq!(model.agent_form.name, "claude-3");
+        assert_eq!(model.agent_form.command, "echo");
+        assert_eq!(model.agent_form.args, "--model 'opus 4'");
+        assert_eq!(model.agent_form.clone_of.as_deref(), Some("claude"));
+        assert_eq!(model.agent_form.target, None);
+    }
+
+    #[test]
+    fn test_move_selected_agent_at_edges() {
+        let mut model = create_test_model();
+        assert!(matches!(
+            move_selected_agent(&mut model, 1),
+            Err(MaestroError::NoAgentSelected)
+        ));
+
+        model.agents.push(create_test_agent("alpha"));
+        model.agents.push(create_test_agent("bravo"));
+        assert!(move_selected_agent(&mut model, -1).is_ok());
+        model.selected_agent = 1;
+        assert!(move_selected_agent(&mut model, 1).is_ok());
+        assert_eq!(model.agents[0].name, "alpha");
+        assert_eq!(model.selected_agent, 1);
+    }
+
     #[test]
     fn persist_agents_keeps_selection_by_name() {
         let mut model = create_test_model();
@@ -1,82 +1,237 @@
 use zellij_tile::prelude::*;
 
-use crate::agent::is_default_agent;
+use crate::agent::check_agent_deletable;
+use crate::config::TabSelection;
 use crate::error::MaestroError;
-use crate::model::Model;
-use crate::ui::{next_field, prev_field, Mode};
+use crate::keymap::Action;
+use crate::model::{HelpView, Model};
+use crate::placement::next_placement_override;
+use crate::template::prompted_variables;
+use crate::ui::{help_visible_entries, next_field, prev_field, AgentFormField, Mode};
+use crate::utils::{build_variant_command, expand_workspace_input};
 
+use super::availability::{request_availability_checks, request_command_check};
 use super::forms::{
-    apply_agent_create, apply_agent_edit, build_agent_from_inputs, handle_form_text,
-    handle_text_edit, persist_agents, start_agent_create, start_agent_delete_confirm,
-    start_agent_edit, start_new_pane_workspace,
+    apply_agent_create, apply_agent_edit, apply_agent_export, apply_agent_import, apply_tab_select,
+    build_agent_from_inputs, confirm_workspace, cycle_prompt_choice, handle_form_text,
+    handle_text_edit, move_selected_agent, persist_agents, refresh_projects,
+    remove_recent_workspace, start_agent_clone, start_agent_create, start_agent_delete_confirm,
+    start_agent_edit, start_agent_export, start_agent_import, start_new_pane_prompts,
+    start_new_pane_tab_select, start_new_pane_workspace, submit_prompt_value, toggle_agent_hidden,
+    toggle_agent_mark,
 };
 use super::panes::{
-    derive_tab_name_from_workspace, focus_selected, kill_selected, spawn_agent_pane, TabChoice,
+    derive_tab_name_from_workspace, focus_selected, focus_session_pane, kill_selected,
+    move_selected_to_tab, relaunch_session_agents, respawn_with_resume, resurrect_session,
+    spawn_agent_pane, start_move_pane, toggle_selected_floating, toggle_selected_pinned,
+    try_selected_agent, TabChoice,
 };
 
 pub fn handle_key_event(model: &mut Model, key: KeyWithModifier) {
-    match model.mode {
-        Mode::View => handle_key_event_view(model, key),
-        Mode::AgentConfig => handle_key_event_agent_config(model, key),
-        Mode::NewPaneWorkspace => handle_key_event_new_pane_workspace(model, key),
-        Mode::NewPaneAgentSelect => handle_key_event_new_pane_agent_select(model, key),
-        Mode::AgentFormCreate | Mode::AgentFormEdit => handle_key_event_agent_form(model, key),
-        Mode::DeleteConfirm => handle_key_event_delete_confirm(model, key),
+    model.notice.clear();
+    let action = model.config.keymap.action(model.mode, &key);
+    if action == Some(Action::Help) && model.mode != Mode::Help {
+        model.help = HelpView {
+            mode: model.mode,
+            scroll: 0,
+        };
+        model.mode = Mode::Help;
+        return;
+    }
+    match (model.mode, action) {
+        (Mode::NewPaneWorkspace, _) => handle_key_event_new_pane_workspace(model, action, &key),
+        (Mode::NewPaneTabSelect, _) => handle_key_event_new_pane_tab_select(model, action, &key),
+        (Mode::NewPanePrompt, _) => handle_key_event_new_pane_prompt(model, action, &key),
+        (Mode::AgentFormCreate | Mode::AgentFormEdit, _) => {
+            handle_key_event_agent_form(model, action, &key)
+        }
+        (Mode::AgentImport | Mode::AgentExport, _) => {
+            handle_key_event_agent_transfer(model, action, &key)
+        }
+        (_, None) => {}
+        (Mode::View, Some(action)) => handle_key_event_view(model, action),
+        (Mode::AllSessions, Some(action)) => handle_key_event_all_sessions(model, action),
+        (Mode::MovePaneTab, Some(action)) => handle_key_event_move_pane_tab(model, action),
+        (Mode::AgentConfig, Some(action)) => handle_key_event_agent_config(model, action),
+        (Mode::NewPaneAgentSelect, Some(action)) => {
+            handle_key_event_new_pane_agent_select(model, action)
+        }
+        (Mode::DeleteConfirm, Some(action)) => handle_key_event_delete_confirm(model, action),
+        (Mode::Help, Some(action)) => handle_key_event_help(model, action),
     }
 }
 
-fn handle_key_event_view(model: &mut Model, key: KeyWithModifier) {
-    match key.bare_key {
-        BareKey::Char('j') => {
+fn handle_key_event_help(model: &mut Model, action: Action) {
+    match action {
+        Action::Down => {
+            let entries = model.config.keymap.help_entries(model.help.mode).len();
+            let last = entries.saturating_sub(help_visible_entries(model.rows));
+            model.help.scroll = (model.help.scroll + 1).min(last);
+        }
+        Action::Up => {
+            model.help.scroll = model.help.scroll.saturating_sub(1);
+        }
+        Action::Back | Action::Help => {
+            model.mode = model.help.mode;
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_event_view(model: &mut Model, action: Action) {
+    match action {
+        Action::Down => {
             move_pane_selection(model, 1);
         }
-        BareKey::Char('k') => {
+        Action::Up => {
             move_pane_selection(model, -1);
         }
-        BareKey::Enter => {
+        Action::Confirm => {
             let idx = model.selected_pane;
             focus_selected(model, idx);
         }
-        BareKey::Esc => {
+        Action::Back => {
             close_self();
         }
-        BareKey::Char('d') => {
+        Action::KillPane => {
             let idx = model.selected_pane;
             kill_selected(model, idx);
         }
-        BareKey::Char('n') => {
+        Action::NewPane => {
             start_new_pane_workspace(model);
         }
-        BareKey::Char('c') => {
+        Action::AgentConfig => {
             model.mode = Mode::AgentConfig;
             model.clear_error();
         }
+        Action::ResumePane => {
+            let idx = model.selected_pane;
+            respawn_with_resume(model, idx);
+        }
+        Action::MovePane => {
+            start_move_pane(model);
+        }
+        Action::ToggleFloating => {
+            toggle_selected_floating(model);
+        }
+        Action::TogglePinned => {
+            toggle_selected_pinned(model);
+        }
+        Action::AllSessions => {
+            model.mode = Mode::AllSessions;
+            model.selected_session_pane = model.selected_pane;
+            model.clamp_selections();
+            model.clear_error();
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_event_move_pane_tab(model: &mut Model, action: Action) {
+    match action {
+        // The last entry of the picker opens a new tab.
+        Action::Down if model.move_tab_idx < model.tab_names.len() => {
+            model.move_tab_idx += 1;
+        }
+        Action::Up => {
+            model.move_tab_idx = model.move_tab_idx.saturating_sub(1);
+        }
+        Action::Confirm => move_selected_to_tab(model),
+        Action::Back => {
+            model.mode = Mode::View;
+            model.clear_error();
+        }
         _ => {}
     }
 }
 
-fn handle_key_event_agent_config(model: &mut Model, key: KeyWithModifier) {
-    match key.bare_key {
-        BareKey::Char('j') => {
+/// The exited session under the cursor in the all-sessions list, if any.
+fn selected_resurrectable_session(model: &Model) -> Option<String> {
+    let idx = model
+        .selected_session_pane
+        .checked_sub(model.all_session_panes().len())?;
+    model.resurrectable_sessions.get(idx).cloned()
+}
+
+fn handle_key_event_all_sessions(model: &mut Model, action: Action) {
+    match action {
+        Action::Down => {
+            let len = model.all_session_panes().len() + model.resurrectable_sessions.len();
+            if model.selected_session_pane + 1 < len {
+                model.selected_session_pane += 1;
+            }
+        }
+        Action::Up => {
+            model.selected_session_pane = model.selected_session_pane.saturating_sub(1);
+        }
+        Action::Confirm => {
+            let idx = model.selected_session_pane;
+            match selected_resurrectable_session(model) {
+                Some(session) => resurrect_session(model, &session),
+                None => focus_session_pane(model, idx),
+            }
+        }
+        Action::RelaunchSession => {
+            if let Some(session) = selected_resurrectable_session(model) {
+                relaunch_session_agents(model, &session);
+            }
+        }
+        Action::Back => {
+            model.mode = Mode::View;
+            model.clear_error();
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_event_agent_config(model: &mut Model, action: Action) {
+    match action {
+        Action::Down => {
             move_agent_selection(model, 1);
         }
-        BareKey::Char('k') => {
+        Action::Up => {
             move_agent_selection(model, -1);
         }
-        BareKey::Char('a') => {
+        Action::AddAgent => {
             start_agent_create(model);
         }
-        BareKey::Char('e') => {
-            if model.selected_agent < model.agents.len() {
-                start_agent_edit(model);
+        Action::EditAgent if model.selected_agent < model.agents.len() => {
+            start_agent_edit(model);
+        }
+        Action::DeleteAgent if model.selected_agent < model.agents.len() => {
+            start_agent_delete_confirm(model);
+        }
+        Action::CloneAgent => {
+            start_agent_clone(model);
+        }
+        Action::TryAgent => {
+            try_selected_agent(model);
+        }
+        Action::MoveAgentDown => {
+            if let Err(err) = move_selected_agent(model, 1) {
+                model.error_message = err.to_string();
+            }
+        }
+        Action::MoveAgentUp => {
+            if let Err(err) = move_selected_agent(model, -1) {
+                model.error_message = err.to_string();
             }
         }
-        BareKey::Char('d') => {
-            if model.selected_agent < model.agents.len() {
-                start_agent_delete_confirm(model);
+        Action::ToggleHidden => {
+            if let Err(err) = toggle_agent_hidden(model) {
+                model.error_message = err.to_string();
             }
         }
-        BareKey::Esc => {
+        Action::MarkAgent => {
+            toggle_agent_mark(model);
+        }
+        Action::ImportAgents => {
+            start_agent_import(model);
+        }
+        Action::ExportAgents => {
+            start_agent_export(model);
+        }
+        Action::Back => {
             model.mode = Mode::View;
             model.clear_error();
         }
@@ -84,95 +239,227 @@ fn handle_key_event_agent_config(model: &mut Model, key: KeyWithModifier) {
     }
 }
 
-fn handle_key_event_new_pane_workspace(model: &mut Model, key: KeyWithModifier) {
-    if handle_text_edit(&mut model.pane_wizard.workspace, &key) {
-        model.pane_wizard.browse_idx = 0;
+fn handle_key_event_new_pane_workspace(
+    model: &mut Model,
+    action: Option<Action>,
+    key: &KeyWithModifier,
+) {
+    let Some(action) = action else {
+        let before = model.pane_wizard.workspace.value().to_string();
+        if handle_text_edit(&mut model.pane_wizard.workspace, key)
+            && model.pane_wizard.workspace.value() != before
+        {
+            model.pane_wizard.browse_idx = 0;
+            model.pane_wizard.create_workspace = None;
+            model.clear_error();
+            model.refresh_workspace_suggestions();
+        }
         return;
-    }
+    };
 
-    let input = model.pane_wizard.workspace.clone();
-    let suggestions = crate::utils::get_path_suggestions(&input);
+    let suggestions = &model.pane_wizard.suggestions;
+    let browsing_recent = model.pane_wizard.workspace.trim().is_empty();
 
-    match key.bare_key {
-        // Pure typedown: no Up/Down navigation, first match is always selected
-        BareKey::Tab => {
+    match action {
+        Action::Down => select_next_suggestion(model),
+        Action::Up => select_prev_suggestion(model),
+        Action::AcceptSuggestion => {
             if let Some(suggestion) = suggestions.get(model.pane_wizard.browse_idx) {
-                model.pane_wizard.workspace = suggestion.clone();
+                model.pane_wizard.workspace.set(suggestion.path.clone());
                 model.pane_wizard.browse_idx = 0;
+                model.refresh_workspace_suggestions();
             }
         }
-        BareKey::Enter => {
-            if let Some(selected) = suggestions.get(model.pane_wizard.browse_idx) {
-                model.pane_wizard.workspace = selected.clone();
+        Action::ToggleHiddenDirs => {
+            model.pane_wizard.show_hidden = !model.pane_wizard.show_hidden;
+            model.pane_wizard.browse_idx = 0;
+            model.refresh_workspace_suggestions();
+        }
+        Action::ForgetWorkspace if browsing_recent => remove_recent_workspace(model),
+        Action::RescanProjects => {
+            refresh_projects(model);
+            model.dir_cache.clear();
+            model.refresh_workspace_suggestions();
+            let count = model.projects.as_ref().map_or(0, Vec::len);
+            model.notice = format!("Found {count} projects");
+        }
+        Action::Confirm => {
+            let ctx = model.path_context();
+            let workspace = match suggestions.get(model.pane_wizard.browse_idx) {
+                Some(selected) => selected.path.clone(),
+                None => expand_workspace_input(&model.pane_wizard.workspace, &ctx)
+                    .trim_end_matches('/')
+                    .to_string(),
+            };
+            model.pane_wizard.workspace.set(workspace);
+            if let Err(err) = confirm_workspace(model) {
+                model.error_message = err.to_string();
+                model.pane_wizard.browse_idx = 0;
+                model.refresh_workspace_suggestions();
+                return;
             }
-            // Always use workspace path as tab name - skip tab selection step
             let tab_name = derive_tab_name_from_workspace(&model.pane_wizard.workspace)
                 .unwrap_or_else(|| crate::utils::default_tab_name(&model.pane_wizard.workspace));
-            model.pane_wizard.tab_name = Some(tab_name);
-            model.mode = Mode::NewPaneAgentSelect;
-            model.pane_wizard.agent_filter = String::new();
-            model.pane_wizard.agent_idx = 0;
-            model.clear_error();
+            match model.config.tab_selection {
+                TabSelection::Workspace => {
+                    model.pane_wizard.tab_name = Some(tab_name);
+                    start_new_pane_agent_select(model);
+                }
+                TabSelection::Current => {
+                    model.pane_wizard.use_current_tab = true;
+                    start_new_pane_agent_select(model);
+                }
+                TabSelection::Ask => start_new_pane_tab_select(model, tab_name),
+            }
         }
-        BareKey::Esc => cancel_to_view(model),
+        Action::Back => cancel_to_view(model),
         _ => {}
     }
 }
 
-fn handle_key_event_new_pane_agent_select(model: &mut Model, key: KeyWithModifier) {
-    let filtered_indices = crate::utils::filter_agents_fuzzy(&model.agents, "");
-    let filtered_count = filtered_indices.len();
+fn select_next_suggestion(model: &mut Model) {
+    if model.pane_wizard.browse_idx + 1 < model.pane_wizard.suggestions.len() {
+        model.pane_wizard.browse_idx += 1;
+        model.resolve_shown_suggestions();
+    }
+}
 
-    match key.bare_key {
-        BareKey::Char('j') => {
-            if filtered_count > 0 && model.pane_wizard.agent_idx + 1 < filtered_count {
-                model.pane_wizard.agent_idx += 1;
-            }
+fn select_prev_suggestion(model: &mut Model) {
+    model.pane_wizard.browse_idx = model.pane_wizard.browse_idx.saturating_sub(1);
+}
+
+fn start_new_pane_agent_select(model: &mut Model) {
+    model.mode = Mode::NewPaneAgentSelect;
+    model.pane_wizard.agent_filter = String::new();
+    model.pane_wizard.agent_idx = 0;
+    model.clear_error();
+}
+
+fn handle_key_event_new_pane_tab_select(
+    model: &mut Model,
+    action: Option<Action>,
+    key: &KeyWithModifier,
+) {
+    let new_tab_idx = model.tab_names.len() + 1;
+    let Some(action) = action else {
+        if handle_text_edit(&mut model.pane_wizard.tab_input, key) {
+            model.pane_wizard.tab_idx = new_tab_idx;
         }
-        BareKey::Char('k') => {
-            if model.pane_wizard.agent_idx > 0 {
-                model.pane_wizard.agent_idx -= 1;
-            }
+        return;
+    };
+    match action {
+        Action::Down if model.pane_wizard.tab_idx < new_tab_idx => {
+            model.pane_wizard.tab_idx += 1;
+        }
+        Action::Up if model.pane_wizard.tab_idx > 0 => {
+            model.pane_wizard.tab_idx -= 1;
+        }
+        Action::Confirm => match apply_tab_select(model) {
+            Ok(()) => start_new_pane_agent_select(model),
+            Err(err) => model.error_message = err.to_string(),
+        },
+        Action::Back => cancel_to_view(model),
+        _ => {}
+    }
+}
+
+fn handle_key_event_new_pane_agent_select(model: &mut Model, action: Action) {
+    let choices = crate::utils::filter_agent_choices(&model.agents, "");
+    let filtered_count = choices.len();
+
+    match action {
+        Action::Down if filtered_count > 0 && model.pane_wizard.agent_idx + 1 < filtered_count => {
+            model.pane_wizard.agent_idx += 1;
         }
-        BareKey::Enter => {
+        Action::Up if model.pane_wizard.agent_idx > 0 => {
+            model.pane_wizard.agent_idx -= 1;
+        }
+        Action::CyclePlacement => {
+            model.pane_wizard.placement = next_placement_override(model.pane_wizard.placement);
+        }
+        Action::Confirm => {
             let selection_idx = model.pane_wizard.agent_idx;
-            if let Some(&agent_idx) = filtered_indices.get(selection_idx) {
-                let agent = model.agents[agent_idx].name.clone();
-                let workspace = model.pane_wizard.workspace.trim().to_string();
-                let tab_name = model
-                    .pane_wizard
-                    .tab_name
-                    .clone()
-                    .unwrap_or_else(|| crate::utils::default_tab_name(&workspace));
-                let tab_choice = if model.tab_names.contains(&tab_name) {
-                    TabChoice::Existing(tab_name)
+            if let Some(choice) = choices.get(selection_idx) {
+                let agent = &model.agents[choice.agent];
+                let variant = choice.variant.and_then(|v| agent.variants.get(v));
+                let prompts = prompted_variables(&build_variant_command(agent, variant));
+                model.pane_wizard.agent_name = Some(choice.label(&model.agents));
+                if prompts.is_empty() {
+                    spawn_from_wizard(model);
                 } else {
-                    TabChoice::New
-                };
-                spawn_agent_pane(model, workspace, agent, tab_choice);
-                if model.error_message.is_empty() {
-                    model.mode = Mode::View;
+                    start_new_pane_prompts(model, prompts);
                 }
             }
         }
-        BareKey::Esc => cancel_to_view(model),
+        Action::Back => cancel_to_view(model),
+        _ => {}
+    }
+}
+
+fn handle_key_event_new_pane_prompt(
+    model: &mut Model,
+    action: Option<Action>,
+    key: &KeyWithModifier,
+) {
+    let Some(action) = action else {
+        handle_text_edit(&mut model.pane_wizard.prompt_input, key);
+        return;
+    };
+    match action {
+        Action::NextChoice => cycle_prompt_choice(model),
+        Action::Confirm => match submit_prompt_value(model) {
+            Ok(true) => spawn_from_wizard(model),
+            Ok(false) => model.clear_error(),
+            Err(err) => model.error_message = err.to_string(),
+        },
+        Action::Back => cancel_to_view(model),
         _ => {}
     }
 }
 
-fn handle_key_event_agent_form(model: &mut Model, key: KeyWithModifier) {
-    if handle_form_text(model, &key) {
+fn spawn_from_wizard(model: &mut Model) {
+    let Some(agent) = model.pane_wizard.agent_name.clone() else {
+        model.error_message = MaestroError::NoAgentSelected.to_string();
         return;
+    };
+    let workspace = model.pane_wizard.workspace.trim().to_string();
+    let tab_name = model
+        .pane_wizard
+        .tab_name
+        .clone()
+        .unwrap_or_else(|| crate::utils::default_tab_name(&workspace));
+    let tab_choice = if model.pane_wizard.use_current_tab {
+        TabChoice::Current
+    } else if model.tab_names.contains(&tab_name) {
+        TabChoice::Existing(tab_name)
+    } else {
+        TabChoice::New
+    };
+    spawn_agent_pane(model, workspace, agent, tab_choice);
+    if model.error_message.is_empty() {
+        model.mode = Mode::View;
     }
-    match key.bare_key {
-        BareKey::Tab => {
-            if key.key_modifiers.contains(&KeyModifier::Shift) {
+}
+
+fn handle_key_event_agent_form(model: &mut Model, action: Option<Action>, key: &KeyWithModifier) {
+    let Some(action) = action else {
+        handle_form_text(model, key);
+        return;
+    };
+    match action {
+        Action::NextField | Action::PrevField => {
+            // Look the command up once it has been typed, not on every keystroke.
+            if model.agent_form.field == AgentFormField::Command {
+                let command = model.agent_form.command.value().to_string();
+                request_command_check(model, &command);
+            }
+            if action == Action::PrevField {
                 model.agent_form.field = prev_field(model.agent_form.field);
             } else {
                 model.agent_form.field = next_field(model.agent_form.field);
             }
         }
-        BareKey::Enter => match build_agent_from_inputs(model) {
+        Action::Confirm => match build_agent_from_inputs(model) {
             Ok(agent) => {
                 let result = match model.mode {
                     Mode::AgentFormEdit => apply_agent_edit(model, agent),
@@ -181,6 +468,7 @@ fn handle_key_event_agent_form(model: &mut Model, key: KeyWithModifier) {
                 };
                 match result {
                     Ok(_) => {
+                        request_availability_checks(model, false);
                         model.mode = Mode::AgentConfig;
                     }
                     Err(err) => {
@@ -192,7 +480,7 @@ fn handle_key_event_agent_form(model: &mut Model, key: KeyWithModifier) {
                 model.error_message = err.to_string();
             }
         },
-        BareKey::Esc => {
+        Action::Back => {
             model.agent_form.clear();
             model.mode = Mode::AgentConfig;
             model.clear_error();
@@ -201,15 +489,14 @@ fn handle_key_event_agent_form(model: &mut Model, key: KeyWithModifier) {
     }
 }
 
-fn handle_key_event_delete_confirm(model: &mut Model, key: KeyWithModifier) {
-    match key.bare_key {
-        BareKey::Enter | BareKey::Char('y') | BareKey::Char('Y') => {
+fn handle_key_event_delete_confirm(model: &mut Model, action: Action) {
+    match action {
+        Action::Confirm | Action::Yes => {
             if let Some(idx) = model.agent_form.target.take() {
                 if idx < model.agents.len() {
-                    let agent_name = model.agents[idx].name.clone();
-                    if is_default_agent(&agent_name) {
-                        model.error_message =
-                            MaestroError::CannotDeleteDefaultAgent(agent_name).to_string();
+                    let agent_name = &model.agents[idx].name;
+                    if let Err(err) = check_agent_deletable(agent_name, &model.config) {
+                        model.error_message = err.to_string();
                         model.mode = Mode::AgentConfig;
                         return;
                     }
@@ -230,13 +517,54 @@ fn handle_key_event_delete_confirm(model: &mut Model, key: KeyWithModifier) {
 
             model.mode = Mode::AgentConfig;
         }
-        BareKey::Esc | BareKey::Char('n') | BareKey::Char('N') => {
+        Action::Back | Action::No => {
             model.mode = Mode::AgentConfig;
         }
         _ => {}
     }
 }
 
+fn handle_key_event_agent_transfer(
+    model: &mut Model,
+    action: Option<Action>,
+    key: &KeyWithModifier,
+) {
+    let Some(action) = action else {
+        handle_text_edit(&mut model.agent_transfer.path, key);
+        return;
+    };
+    match action {
+        Action::CycleConflict if model.mode == Mode::AgentImport => {
+            model.agent_transfer.strategy = model.agent_transfer.strategy.next();
+        }
+        Action::Confirm => {
+            let result = match model.mode {
+                Mode::AgentImport => apply_agent_import(model),
+                Mode::AgentExport => apply_agent_export(model),
+                _ => Err(MaestroError::InvalidMode),
+            };
+            match result {
+                Ok(notice) => {
+                    if model.mode == Mode::AgentImport {
+                        request_availability_checks(model, false);
+                    }
+                    model.notice = notice;
+                    model.mode = Mode::AgentConfig;
+                }
+                Err(err) => {
+                    model.error_message = err.to_string();
+                }
+            }
+        }
+        Action::Back => {
+            model.agent_transfer.clear();
+            model.mode = Mode::AgentConfig;
+            model.clear_error();
+        }
+        _ => {}
+    }
+}
+
 fn cancel_to_view(model: &mut Model) {
     model.mode = Mode::View;
     model.pane_wizard.clear();
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use zellij_tile::prelude::*;
+
+use super::context::CTX_ACTION;
+use crate::history::now_secs;
+use crate::model::Model;
+use crate::template::parse_branch;
+use crate::ui::Mode;
+use crate::utils::{host_relative_arg, parse_dir_listing};
+
+pub(super) const ACTION_LIST_DIR: &str = "list_dir";
+pub(super) const ACTION_GIT_BRANCH: &str = "git_branch";
+const CTX_DIR: &str = "dir";
+
+/// `find` arguments listing the directories directly in the searched one,
+/// NUL-separated. Symlinks count when they point at a directory.
+const FIND_DIRS: &[&str] = &[
+    "-mindepth",
+    "1",
+    "-maxdepth",
+    "1",
+    "(",
+    "-type",
+    "d",
+    "-o",
+    "-type",
+    "l",
+    "-exec",
+    "test",
+    "-d",
+    "{}",
+    ";",
+    ")",
+    "-print0",
+];
+
+/// List the directories the workspace suggestions asked for, in the
+/// background so a large or slow directory never blocks a keypress.
+pub fn request_dir_listings(model: &mut Model) {
+    if !model.permissions_granted {
+        return;
+    }
+    for dir in model.dir_cache.take_requests() {
+        let mut ctx = BTreeMap::new();
+        ctx.insert(CTX_ACTION.to_string(), ACTION_LIST_DIR.to_string());
+        ctx.insert(CTX_DIR.to_string(), dir.to_string_lossy().into_owned());
+        let arg = host_relative_arg(&dir);
+        let mut cmd = vec!["find", arg.as_str()];
+        cmd.extend(FIND_DIRS);
+        run_command(&cmd, ctx);
+    }
+}
+
+/// Look up the git branches of the workspaces chosen since the last event.
+pub fn request_git_branches(model: &mut Model) {
+    if !model.permissions_granted {
+        return;
+    }
+    for dir in model.branches.take_requests() {
+        let mut ctx = BTreeMap::new();
+        ctx.insert(CTX_ACTION.to_string(), ACTION_GIT_BRANCH.to_string());
+        ctx.insert(CTX_DIR.to_string(), dir.to_string_lossy().into_owned());
+        let arg = host_relative_arg(&dir);
+        run_command(
+            &["git", "-C", &arg, "symbolic-ref", "--short", "-q", "HEAD"],
+            ctx,
+        );
+    }
+}
+
+/// Store the branch a finished lookup printed.
+pub(super) fn record_git_branch(
+    model: &mut Model,
+    exit_code: Option<i32>,
+    stdout: &[u8],
+    ctx: &BTreeMap<String, String>,
+) {
+    if let Some(dir) = ctx.get(CTX_DIR) {
+        model
+            .branches
+            .fill(Path::new(dir), parse_branch(exit_code, stdout));
+    }
+}
+
+/// Store a finished listing and refresh the suggestions waiting on it.
+pub(super) fn record_dir_listing(
+    model: &mut Model,
+    exit_code: Option<i32>,
+    stdout: &[u8],
+    ctx: &BTreeMap<String, String>,
+) {
+    let Some(dir) = ctx.get(CTX_DIR) else {
+        return;
+    };
+    let dir = Path::new(dir);
+    let entries = if exit_code == Some(0) {
+        parse_dir_listing(dir, stdout)
+    } else {
+        Vec::new()
+    };
+    model.dir_cache.fill(dir, entries, now_secs());
+    if model.mode == Mode::NewPaneWorkspace {
+        model.refresh_workspace_suggestions();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_result_fills_cache_and_suggestions() {
+        let mut model = Model {
+            mode: Mode::NewPaneWorkspace,
+            ..Default::default()
+        };
+        model.pane_wizard.workspace = "/host/src/ma".into();
+        model.refresh_workspace_suggestions();
+        assert!(model.pane_wizard.loading);
+        assert_eq!(
+            model.dir_cache.take_requests(),
+            vec![Path::new("/host/src").to_path_buf()]
+        );
+
+        let mut ctx = BTreeMap::new();
+        ctx.insert(CTX_ACTION.to_string(), ACTION_LIST_DIR.to_string());
+        ctx.insert(CTX_DIR.to_string(), "/host/src".to_string());
+        record_dir_listing(&mut model, Some(0), b"./src/maestro\0./src/zellij\0", &ctx);
+
+        assert!(!model.pane_wizard.loading);
+        let paths: Vec<&str> = model
+            .pane_wizard
+            .suggestions
+            .iter()
+            .map(|s| s.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["/host/src/maestro"]);
+    }
+}
@@ -1,11 +1,17 @@
 //! Event handlers for Zellij plugin events.
 
+mod availability;
+mod context;
 mod forms;
 mod keys;
+mod listing;
 mod panes;
 mod session;
 
+pub use availability::{handle_run_command_result, request_availability_checks};
+pub use forms::handle_paste;
 pub use keys::handle_key_event;
+pub use listing::{request_dir_listings, request_git_branches};
 pub use panes::{focus_selected, kill_selected, spawn_agent_pane, TabChoice};
 pub use session::{
     apply_pane_update, apply_tab_update, handle_command_pane_exited, handle_command_pane_opened,
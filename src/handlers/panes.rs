@@ -1,17 +1,32 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 use uuid::Uuid;
 use zellij_tile::prelude::*;
 
-use crate::agent::names_match;
-use crate::error::MaestroError;
+use crate::agent::{names_match, split_variant_name, Agent, AgentPane, Availability, PaneStatus};
+use crate::error::{MaestroError, MaestroResult};
+use crate::history::now_secs;
+use crate::layout::{render_layout, RenderedLayout};
 use crate::model::Model;
-use crate::utils::{build_command, workspace_basename};
+use crate::placement::{Placement, PlacementKind};
+use crate::registry::PaneRecord;
+use crate::template::{expand_command, today, TemplateContext};
+use crate::ui::Mode;
+use crate::utils::{build_variant_command, host_fs_path, validate_workspace, workspace_basename};
+
+/// Context keys carrying the placement of a spawned pane to `CommandPaneOpened`.
+pub(super) const CTX_PLACEMENT: &str = "placement";
+pub(super) const CTX_ANCHOR: &str = "anchor_pane";
+/// Context key naming the agent of a pane opened by the try action.
+pub(super) const CTX_TRIAL: &str = "trial_agent";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TabChoice {
     Existing(String),
     New,
+    /// The active tab, without switching.
+    Current,
 }
 
 pub(super) fn derive_tab_name_from_workspace(input: &str) -> Option<String> {
@@ -36,71 +51,434 @@ pub fn spawn_agent_pane(
         return;
     }
 
-    let cmd = match model
+    let (base_name, variant_name) = split_variant_name(&agent_name);
+    let agent = model
         .agents
         .iter()
-        .find(|a| names_match(&a.name, &agent_name))
-    {
-        Some(a) => build_command(a),
-        None => {
+        .find(|a| names_match(&a.name, base_name));
+    let variant = match (agent, variant_name) {
+        (Some(a), Some(v)) => a.find_variant(v).map(Some),
+        (Some(_), None) => Some(None),
+        (None, _) => None,
+    };
+    let (agent, variant) = match (agent, variant) {
+        (Some(a), Some(v)) => (a.clone(), v.cloned()),
+        _ => {
             model.error_message = MaestroError::AgentNotFound(agent_name).to_string();
             return;
         }
     };
 
-    let workspace_label = workspace_basename(&workspace_path);
-    let title_label = if workspace_label.is_empty() {
-        &agent_name
-    } else {
-        &workspace_label
-    };
-    let title = format!("{}:{}", title_label, Uuid::new_v4());
+    if let Err(err) = validate_workspace(&workspace_path) {
+        model.error_message = err.to_string();
+        return;
+    }
+    // The availability check only sees the plugin's `PATH`, so a missing
+    // command is a warning rather than a reason to refuse the spawn.
+    let warning = missing_command_warning(model, &agent);
+
+    if let Some(layout) = agent.layout.as_deref() {
+        // A layout always opens its own tab; Zellij cannot apply one inside
+        // an existing tab, so a taken name gets a numbered suffix instead.
+        let requested = match tab_choice {
+            TabChoice::Existing(name) => name,
+            TabChoice::New | TabChoice::Current => wizard_tab_name(model, &workspace_path),
+        };
+        let tab_name = unique_tab_name(&model.tab_names, &requested);
+        let template_ctx = template_context(model, &workspace_path, &tab_name);
+        let cmd = expand_command(
+            &build_variant_command(&agent, variant.as_ref()),
+            &template_ctx,
+        );
+        match open_layout_tab(
+            model,
+            &agent_name,
+            &workspace_path,
+            &tab_name,
+            layout,
+            &template_ctx,
+            cmd,
+        ) {
+            Ok(()) => {
+                model.workspace_history.record(&workspace_path, now_secs());
+                model.clear_error();
+                model.pane_wizard.clear();
+                if tab_name != requested {
+                    model.notice = format!("Layouts open a new tab: opened {tab_name}");
+                }
+                if let Some(warning) = warning {
+                    model.notice = warning;
+                }
+            }
+            Err(err) => model.error_message = err.to_string(),
+        }
+        return;
+    }
 
     let resolved_workspace = crate::utils::resolve_workspace_path(&workspace_path);
 
     let tab_target = match tab_choice {
         TabChoice::Existing(name) => name,
         TabChoice::New => {
-            let name = model
-                .pane_wizard
-                .tab_name
-                .as_ref()
-                .filter(|s| !s.trim().is_empty())
-                .cloned()
-                .unwrap_or_else(|| crate::utils::default_tab_name(&workspace_path));
-            let cwd_for_tab = resolved_workspace
-                .as_ref()
-                .map(|p| p.to_string_lossy().into_owned());
-            new_tab(Some(name.clone()), cwd_for_tab);
-            if !model.tab_names.contains(&name) {
-                model.tab_names.push(name.clone());
-            }
+            let name = wizard_tab_name(model, &workspace_path);
+            open_tab(model, &name, resolved_workspace.as_ref());
             name
         }
+        TabChoice::Current => model.current_tab.clone().unwrap_or_default(),
     };
 
-    go_to_tab_name(&tab_target);
+    if !tab_target.is_empty() {
+        go_to_tab_name(&tab_target);
+    }
 
-    let mut ctx = BTreeMap::new();
-    ctx.insert("pane_title".to_string(), title.clone());
-    if let Some(ref resolved) = resolved_workspace {
-        ctx.insert("cwd".to_string(), resolved.to_string_lossy().into_owned());
+    let template_ctx = template_context(model, &workspace_path, &tab_target);
+    let cmd = expand_command(
+        &build_variant_command(&agent, variant.as_ref()),
+        &template_ctx,
+    );
+    let placement = match model.pane_wizard.placement {
+        Some(kind) => placement_with_kind(model, &agent_name, kind),
+        None => placement_for(model, &agent_name),
+    };
+    open_agent_pane(
+        model,
+        &agent_name,
+        &workspace_path,
+        &tab_target,
+        cmd,
+        &[],
+        &placement,
+    );
+
+    model.workspace_history.record(&workspace_path, now_secs());
+    model.clear_error();
+    model.pane_wizard.clear();
+    if let Some(warning) = warning {
+        model.notice = warning;
     }
-    ctx.insert("agent".to_string(), agent_name.clone());
-    ctx.insert("tab_name".to_string(), tab_target.clone());
+}
+
+/// Warning shown after spawning an agent whose command was not found.
+fn missing_command_warning(model: &Model, agent: &Agent) -> Option<String> {
+    (model.agent_availability(agent) == Availability::Missing).then(|| {
+        format!(
+            "Warning: {}; the pane may fail to start",
+            MaestroError::AgentCommandMissing(agent.command.clone())
+        )
+    })
+}
+
+/// `base`, or `base 2`, `base 3`, ... when a tab of that name exists.
+fn unique_tab_name(tab_names: &[String], base: &str) -> String {
+    let taken = |name: &str| tab_names.iter().any(|t| t == name);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base} {n}"))
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+/// Tab name chosen in the wizard, or one derived from the workspace.
+fn wizard_tab_name(model: &Model, workspace_path: &str) -> String {
+    model
+        .pane_wizard
+        .tab_name
+        .as_ref()
+        .filter(|s| !s.trim().is_empty())
+        .cloned()
+        .unwrap_or_else(|| crate::utils::default_tab_name(workspace_path))
+}
+
+fn open_tab(model: &mut Model, name: &str, cwd: Option<&PathBuf>) {
+    new_tab(
+        Some(name.to_string()),
+        cwd.map(|p| p.to_string_lossy().into_owned()),
+    );
+    if !model.tab_names.iter().any(|t| t == name) {
+        model.tab_names.push(name.to_string());
+    }
+}
 
+/// Placement for a new pane of `agent_name` (a display name, possibly with a
+/// variant): the agent's own placement, else the global default.
+pub(super) fn placement_for(model: &Model, agent_name: &str) -> Placement {
+    let (base_name, _) = split_variant_name(agent_name);
+    model
+        .agents
+        .iter()
+        .find(|a| names_match(&a.name, base_name))
+        .and_then(|a| a.placement.clone())
+        .unwrap_or_else(|| model.config.placement.clone())
+}
+
+/// Placement of `kind` picked in the wizard, keeping the configured floating
+/// geometry when the configured placement is of the same kind.
+pub(super) fn placement_with_kind(
+    model: &Model,
+    agent_name: &str,
+    kind: PlacementKind,
+) -> Placement {
+    let configured = placement_for(model, agent_name);
+    if configured.kind == kind {
+        configured
+    } else {
+        Placement::new(kind)
+    }
+}
+
+fn command_to_run(cmd: Vec<String>, cwd: Option<PathBuf>) -> CommandToRun {
     let mut command_to_run = if cmd.len() > 1 {
         CommandToRun::new_with_args(cmd[0].clone(), cmd[1..].to_vec())
     } else {
         CommandToRun::new(cmd.into_iter().next().unwrap_or_default())
     };
+    command_to_run.cwd = cwd;
+    command_to_run
+}
+
+/// Run the selected agent in a floating pane in the current workspace to
+/// check that its command works. The pane is not tracked as an agent pane;
+/// its exit status is reported back to the config screen.
+pub(super) fn try_selected_agent(model: &mut Model) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+    let Some(agent) = model.agents.get(model.selected_agent).cloned() else {
+        model.error_message = MaestroError::NoAgentSelected.to_string();
+        return;
+    };
+    let workspace = model.focused_workspace().unwrap_or_default().to_string();
+    let tab = model.current_tab.clone().unwrap_or_default();
+    let mut template_ctx = template_context(model, &workspace, &tab);
+    // Nothing is prompted for a try; prompted placeholders take their defaults.
+    template_ctx.prompted.clear();
+    let cmd = expand_command(&build_variant_command(&agent, None), &template_ctx);
+
+    let mut ctx = BTreeMap::new();
+    ctx.insert(CTX_TRIAL.to_string(), agent.name.clone());
+    let cwd = crate::utils::resolve_workspace_path(&workspace);
+    open_command_pane_floating(command_to_run(cmd, cwd), None, ctx);
+    // Like relative workspace paths, a try runs in the focused agent pane's
+    // workspace and otherwise in the plugin `cwd`; say which one it got.
+    let location = if workspace.is_empty() {
+        "the plugin cwd".to_string()
+    } else {
+        crate::utils::display_workspace(&workspace).to_string()
+    };
+    model.notice = format!("Trying {} in a floating pane in {location}", agent.name);
+    model.clear_error();
+}
+
+/// Open a command pane running `cmd` in the focused tab at `placement`,
+/// titled with a fresh UUID and recorded in the pane registry. `resume_args`
+/// are appended to the command run but not to the recorded one, so resuming
+/// the pane again does not repeat them.
+fn open_agent_pane(
+    model: &mut Model,
+    agent_name: &str,
+    workspace_path: &str,
+    tab_name: &str,
+    cmd: Vec<String>,
+    resume_args: &[String],
+    placement: &Placement,
+) {
+    let pane_id = Uuid::new_v4().to_string();
+    let title = format!("{}:{}", title_label(agent_name, workspace_path), pane_id);
+
+    let resolved_workspace = crate::utils::resolve_workspace_path(workspace_path);
+
+    let mut ctx = BTreeMap::new();
+    ctx.insert("pane_title".to_string(), title.clone());
     if let Some(ref resolved) = resolved_workspace {
-        command_to_run.cwd = Some(resolved.clone());
+        ctx.insert("cwd".to_string(), resolved.to_string_lossy().into_owned());
+    }
+    ctx.insert("agent".to_string(), agent_name.to_string());
+    ctx.insert("tab_name".to_string(), tab_name.to_string());
+    ctx.insert(
+        CTX_PLACEMENT.to_string(),
+        placement.kind.label().to_string(),
+    );
+    let anchor = model.focused_panes.get(tab_name).copied();
+    if let Some(anchor) = anchor {
+        ctx.insert(CTX_ANCHOR.to_string(), anchor.to_string());
+    }
+
+    let cmd_line = cmd.clone();
+    let run = cmd.into_iter().chain(resume_args.iter().cloned()).collect();
+    let command_to_run = command_to_run(run, resolved_workspace.clone());
+    match placement.kind {
+        PlacementKind::Floating => {
+            open_command_pane_floating(command_to_run, placement.coordinates(), ctx)
+        }
+        PlacementKind::InPlace => {
+            if let Some(anchor) = anchor {
+                focus_terminal_pane(anchor, false);
+            }
+            open_command_pane_in_place(command_to_run, ctx);
+        }
+        PlacementKind::Tiled | PlacementKind::Stacked => open_command_pane(command_to_run, ctx),
+    }
+
+    model.registry.insert(PaneRecord {
+        id: pane_id,
+        session: model.session_name.clone().unwrap_or_default(),
+        pane_id: None,
+        agent_name: agent_name.to_string(),
+        workspace_path: resolved_workspace
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        tab_name: tab_name.to_string(),
+        command: cmd_line,
+    });
+}
+
+/// Label in front of the UUID in an agent pane title: the workspace name,
+/// else the agent name.
+fn title_label(agent_name: &str, workspace_path: &str) -> String {
+    let workspace_label = workspace_basename(workspace_path);
+    if workspace_label.is_empty() {
+        agent_name.to_string()
+    } else {
+        workspace_label
+    }
+}
+
+/// Open the agent's layout as a new tab and track the agent panes it places.
+fn open_layout_tab(
+    model: &mut Model,
+    agent_name: &str,
+    workspace_path: &str,
+    tab_name: &str,
+    layout: &str,
+    template_ctx: &TemplateContext,
+    cmd: Vec<String>,
+) -> MaestroResult<()> {
+    let label = title_label(agent_name, workspace_path);
+    let cwd = Some(template_ctx.workspace.as_str()).filter(|w| !w.is_empty());
+    let rendered = render_layout(layout, tab_name, cwd, template_ctx, &cmd, || label.clone())?;
+    new_tabs_with_layout(&rendered.kdl);
+    if !model.tab_names.iter().any(|t| t == tab_name) {
+        model.tab_names.push(tab_name.to_string());
+    }
+    track_layout_panes(model, agent_name, workspace_path, tab_name, &rendered, &cmd);
+    Ok(())
+}
+
+/// Record the agent panes of a rendered layout. Zellij announces no ids for
+/// layout panes, so they are matched by command once they are listed.
+fn track_layout_panes(
+    model: &mut Model,
+    agent_name: &str,
+    workspace_path: &str,
+    tab_name: &str,
+    rendered: &RenderedLayout,
+    cmd: &[String],
+) {
+    let workspace = crate::utils::resolve_workspace_path(workspace_path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    for _ in &rendered.agent_titles {
+        model.registry.insert(PaneRecord {
+            id: Uuid::new_v4().to_string(),
+            session: model.session_name.clone().unwrap_or_default(),
+            pane_id: None,
+            agent_name: agent_name.to_string(),
+            workspace_path: workspace.clone(),
+            tab_name: tab_name.to_string(),
+            command: cmd.to_vec(),
+        });
     }
-    open_command_pane(command_to_run, ctx);
+}
 
+/// Resurrect an exited session by switching to it.
+pub fn resurrect_session(model: &mut Model, session: &str) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+    switch_session(Some(session));
+    model.mode = Mode::View;
     model.clear_error();
-    model.pane_wizard.clear();
+}
+
+/// Relaunch the agents an exited session was running into fresh panes of the
+/// current session, in their original workspace and tab, with the command
+/// line they were started with.
+pub fn relaunch_session_agents(model: &mut Model, session: &str) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+
+    let records: Vec<PaneRecord> = model
+        .registry
+        .session_records(session)
+        .into_iter()
+        .filter(|r| !r.command.is_empty())
+        .cloned()
+        .collect();
+    if records.is_empty() {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    }
+
+    for record in &records {
+        let tab_name = if record.tab_name.is_empty() {
+            crate::utils::default_tab_name(&record.workspace_path)
+        } else {
+            record.tab_name.clone()
+        };
+        if !model.tab_names.contains(&tab_name) {
+            let cwd = crate::utils::resolve_workspace_path(&record.workspace_path);
+            open_tab(model, &tab_name, cwd.as_ref());
+        }
+        go_to_tab_name(&tab_name);
+        let placement = placement_for(model, &record.agent_name);
+        open_agent_pane(
+            model,
+            &record.agent_name,
+            &record.workspace_path,
+            &tab_name,
+            record.command.clone(),
+            &[],
+            &placement,
+        );
+    }
+
+    model.registry.remove_session(session);
+    model.resurrectable_sessions.retain(|s| s != session);
+    model.notice = format!("Relaunched {} agent(s) from {session}", records.len());
+    model.mode = Mode::View;
+    model.clear_error();
+    model.clamp_selections();
+}
+
+/// Gather placeholder values for an agent spawned into `workspace_path` and `tab`.
+pub(super) fn template_context(model: &Model, workspace_path: &str, tab: &str) -> TemplateContext {
+    let workspace_path = workspace_path.trim().trim_end_matches('/');
+    let resolved = crate::utils::resolve_workspace_path(workspace_path);
+    let workspace = match (&model.host_cwd, resolved) {
+        (Some(cwd), Some(rel)) => cwd.join(rel),
+        (Some(cwd), None) => cwd.clone(),
+        (None, Some(rel)) => rel,
+        (None, None) => PathBuf::new(),
+    };
+    TemplateContext {
+        workspace: workspace.to_string_lossy().into_owned(),
+        workspace_name: workspace_basename(workspace_path),
+        tab: tab.to_string(),
+        session: model.session_name.clone().unwrap_or_default(),
+        branch: model
+            .branches
+            .get(&host_fs_path(workspace_path))
+            .unwrap_or_default()
+            .to_string(),
+        date: today(),
+        prompted: model.pane_wizard.prompt_values.clone(),
+    }
 }
 
 pub fn focus_selected(model: &mut Model, selected_idx: usize) {
@@ -123,6 +501,243 @@ pub fn focus_selected(model: &mut Model, selected_idx: usize) {
     }
 }
 
+/// Focus an entry of the all-sessions list, switching session if it lives elsewhere.
+pub fn focus_session_pane(model: &mut Model, selected_idx: usize) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+
+    let entries = model.all_session_panes();
+    let Some(&(session, pane)) = entries.get(selected_idx) else {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    };
+    let Some(pid) = pane.pane_id else {
+        model.error_message = MaestroError::PaneIdUnavailable.to_string();
+        return;
+    };
+    let Some(other) = model.other_sessions.iter().find(|s| s.name == session) else {
+        // A pane of the current session: select it in the main list by id.
+        let Some(idx) = model
+            .agent_panes
+            .iter()
+            .position(|p| p.pane_id == Some(pid))
+        else {
+            model.error_message = MaestroError::NoAgentPanes.to_string();
+            return;
+        };
+        model.mode = Mode::View;
+        model.selected_pane = idx;
+        focus_selected(model, idx);
+        return;
+    };
+    let tab_position = other.tab_names.iter().position(|t| t == &pane.tab_name);
+    switch_session_with_focus(session, tab_position, Some((pid, false)));
+    model.mode = Mode::View;
+    model.clear_error();
+}
+
+/// Relaunch an exited agent pane with its agent's resume arguments so it
+/// picks up the previous conversation, replacing the exited pane.
+pub fn respawn_with_resume(model: &mut Model, selected_idx: usize) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+    let Some(pane) = model.agent_panes.get(selected_idx).cloned() else {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    };
+    let (workspace, cmd, resume_args) = match resume_command(model, &pane) {
+        Ok(resume) => resume,
+        Err(err) => {
+            model.error_message = err.to_string();
+            return;
+        }
+    };
+
+    go_to_tab_name(&pane.tab_name);
+    let placement = placement_for(model, &pane.agent_name);
+    open_agent_pane(
+        model,
+        &pane.agent_name,
+        &workspace,
+        &pane.tab_name,
+        cmd,
+        &resume_args,
+        &placement,
+    );
+    if let Some(pid) = pane.pane_id {
+        close_terminal_pane(pid);
+    }
+    model.agent_panes.remove(selected_idx);
+    model.clear_error();
+    model.clamp_selections();
+}
+
+/// Workspace, command line and resume args for resuming an exited pane. The
+/// command is the one the pane was spawned with (or its agent's current
+/// command), without resume args a previous resume left in the record.
+pub(super) fn resume_command(
+    model: &Model,
+    pane: &AgentPane,
+) -> MaestroResult<(String, Vec<String>, Vec<String>)> {
+    if pane.status == PaneStatus::Running {
+        return Err(MaestroError::PaneStillRunning);
+    }
+    let (base_name, variant_name) = split_variant_name(&pane.agent_name);
+    let agent = model
+        .agents
+        .iter()
+        .find(|a| names_match(&a.name, base_name))
+        .ok_or_else(|| MaestroError::AgentNotFound(pane.agent_name.clone()))?;
+    if agent.resume_args.is_empty() {
+        return Err(MaestroError::ResumeNotConfigured(agent.name.clone()));
+    }
+
+    let session = model.session_name.clone().unwrap_or_default();
+    let record = pane
+        .pane_id
+        .and_then(|id| model.registry.find_by_pane_id(&session, id))
+        .or_else(|| model.registry.find_by_title(&pane.pane_title))
+        .filter(|r| !r.command.is_empty());
+    let (workspace, cmd) = match record {
+        Some(record) => (record.workspace_path.clone(), record.command.clone()),
+        None => {
+            let variant = variant_name.and_then(|v| agent.find_variant(v));
+            let template_ctx = template_context(model, &pane.workspace_path, &pane.tab_name);
+            (
+                pane.workspace_path.clone(),
+                expand_command(&build_variant_command(agent, variant), &template_ctx),
+            )
+        }
+    };
+    Ok((workspace, cmd, agent.resume_args.clone()))
+}
+
+/// Open the tab picker for moving the selected pane.
+pub fn start_move_pane(model: &mut Model) {
+    let Some(pane) = model.agent_panes.get(model.selected_pane) else {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    };
+    model.move_tab_idx = model
+        .tab_names
+        .iter()
+        .position(|t| t == &pane.tab_name)
+        .unwrap_or(0);
+    model.mode = Mode::MovePaneTab;
+    model.clear_error();
+}
+
+/// Move the selected pane to the tab highlighted in the tab picker, or to a
+/// new tab named after its workspace.
+pub fn move_selected_to_tab(model: &mut Model) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+    let idx = model.selected_pane;
+    let Some(pane) = model.agent_panes.get(idx) else {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    };
+    let Some(pid) = pane.pane_id else {
+        model.error_message = MaestroError::PaneIdUnavailable.to_string();
+        return;
+    };
+    if is_move_target(model, &pane.tab_name) {
+        // Already in that tab; breaking it out would only reorder the tab.
+        model.mode = Mode::View;
+        model.clear_error();
+        return;
+    }
+
+    let tab_name = match model.tab_names.get(model.move_tab_idx) {
+        Some(name) => {
+            break_panes_to_tab_with_index(&[PaneId::Terminal(pid)], model.move_tab_idx, false);
+            name.clone()
+        }
+        None => {
+            let name = crate::utils::default_tab_name(&pane.workspace_path);
+            break_panes_to_new_tab(&[PaneId::Terminal(pid)], Some(name.clone()), false);
+            model.tab_names.push(name.clone());
+            name
+        }
+    };
+    set_pane_tab(model, idx, &tab_name);
+    model.mode = Mode::View;
+    model.clear_error();
+}
+
+/// Whether the tab highlighted in the move step is `tab_name`.
+fn is_move_target(model: &Model, tab_name: &str) -> bool {
+    model
+        .tab_names
+        .get(model.move_tab_idx)
+        .is_some_and(|t| t == tab_name)
+}
+
+/// Record that the pane at `idx` now lives in `tab_name`.
+pub(super) fn set_pane_tab(model: &mut Model, idx: usize, tab_name: &str) {
+    let session = model.session_name.clone().unwrap_or_default();
+    let Some(pane) = model.agent_panes.get_mut(idx) else {
+        return;
+    };
+    pane.tab_name = tab_name.to_string();
+    pane.pending_tab_index = None;
+    // A moved pane leaves any floating layer behind.
+    pane.floating = false;
+    pane.pinned = false;
+    if let Some(pid) = pane.pane_id {
+        model.registry.set_tab(&session, pid, tab_name);
+    }
+}
+
+/// Toggle the selected pane between floating and embedded.
+pub fn toggle_selected_floating(model: &mut Model) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+    let Some(pane) = model.agent_panes.get(model.selected_pane) else {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    };
+    let Some(pid) = pane.pane_id else {
+        model.error_message = MaestroError::PaneIdUnavailable.to_string();
+        return;
+    };
+    // The floating flag follows the next PaneUpdate, once Zellij has
+    // actually moved the pane.
+    toggle_pane_embed_or_eject_for_pane_id(PaneId::Terminal(pid));
+    model.clear_error();
+}
+
+/// Pin or unpin the selected floating pane.
+pub fn toggle_selected_pinned(model: &mut Model) {
+    if !model.permissions_granted {
+        model.error_message = MaestroError::PermissionsNotGranted.to_string();
+        return;
+    }
+    let Some(pane) = model.agent_panes.get_mut(model.selected_pane) else {
+        model.error_message = MaestroError::NoAgentPanes.to_string();
+        return;
+    };
+    let Some(pid) = pane.pane_id else {
+        model.error_message = MaestroError::PaneIdUnavailable.to_string();
+        return;
+    };
+    if !pane.floating {
+        model.error_message = MaestroError::PaneNotFloating.to_string();
+        return;
+    }
+    pane.pinned = !pane.pinned;
+    set_floating_pane_pinned(PaneId::Terminal(pid), pane.pinned);
+    model.clear_error();
+}
+
 pub fn kill_selected(model: &mut Model, selected_idx: usize) {
     if !model.permissions_granted {
         model.error_message = MaestroError::PermissionsNotGranted.to_string();
@@ -161,6 +776,227 @@ mod tests {
         assert_eq!(derived, Some("src/maestro".to_string()));
     }
 
+    #[test]
+    fn test_unique_tab_name() {
+        let tabs = vec!["app".to_string(), "app 2".to_string()];
+        assert_eq!(unique_tab_name(&tabs, "api"), "api");
+        assert_eq!(unique_tab_name(&tabs, "app"), "app 3");
+    }
+
+    #[test]
+    fn test_missing_command_warning() {
+        let mut model = Model::default();
+        let agent = Agent {
+            name: "codex".to_string(),
+            command: "codex".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(missing_command_warning(&model, &agent), None);
+
+        model
+            .command_availability
+            .insert("codex".to_string(), Availability::Missing);
+        assert_eq!(
+            missing_command_warning(&model, &agent).as_deref(),
+            Some("Warning: Command not found: codex; the pane may fail to start")
+        );
+    }
+
+    #[test]
+    fn test_template_context_joins_host_cwd() {
+        let mut model = Model {
+            host_cwd: Some(PathBuf::from("/home/me")),
+            session_name: Some("work".to_string()),
+            ..Default::default()
+        };
+        model
+            .pane_wizard
+            .prompt_values
+            .insert("model".to_string(), "opus".to_string());
+
+        let ctx = template_context(&model, &format!("{}/src/app/", WASI_HOST_MOUNT), "app");
+        assert_eq!(ctx.workspace, "/home/me/src/app");
+        assert_eq!(ctx.workspace_name, "app");
+        assert_eq!(ctx.tab, "app");
+        assert_eq!(ctx.session, "work");
+        assert_eq!(ctx.prompted.get("model").map(String::as_str), Some("opus"));
+
+        let ctx = template_context(&model, "", "main");
+        assert_eq!(ctx.workspace, "/home/me");
+    }
+
+    #[test]
+    fn test_track_layout_panes() {
+        let mut model = Model {
+            session_name: Some("work".to_string()),
+            ..Default::default()
+        };
+        let rendered = RenderedLayout {
+            kdl: String::new(),
+            agent_titles: vec!["app".to_string(), "app".to_string()],
+        };
+        let cmd = vec!["claude".to_string()];
+        track_layout_panes(
+            &mut model,
+            "claude",
+            &format!("{}/src/app", WASI_HOST_MOUNT),
+            "app",
+            &rendered,
+            &cmd,
+        );
+
+        // Panes show up once Zellij lists them.
+        assert!(model.agent_panes.is_empty());
+        let records = model.registry.records();
+        assert_eq!(records.len(), 2);
+        assert_ne!(records[0].id, records[1].id);
+        assert_eq!(records[0].session, "work");
+        assert_eq!(records[0].workspace_path, "src/app");
+        assert_eq!(records[0].command, cmd);
+        assert_eq!(records[0].pane_id, None);
+    }
+
+    #[test]
+    fn test_resume_command() {
+        let mut agent = crate::test_helpers::create_test_agent("claude");
+        agent.resume_args = vec!["--continue".to_string()];
+        let mut model = Model {
+            agents: vec![agent, crate::test_helpers::create_test_agent("gemini")],
+            ..Default::default()
+        };
+        let mut pane = AgentPane {
+            pane_title: "app:4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10".to_string(),
+            tab_name: "app".to_string(),
+            pane_id: Some(2),
+            workspace_path: "src/app".to_string(),
+            agent_name: "claude".to_string(),
+            status: PaneStatus::Exited(Some(1)),
+            ..Default::default()
+        };
+
+        let (workspace, cmd, resume_args) = resume_command(&model, &pane).unwrap();
+        assert_eq!(workspace, "src/app");
+        assert_eq!(cmd, vec!["echo", "claude"]);
+        assert_eq!(resume_args, vec!["--continue"]);
+
+        // The recorded command line wins over the agent's current one.
+        model.registry.insert(PaneRecord {
+            id: "4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10".to_string(),
+            workspace_path: "src/other".to_string(),
+            command: vec![
+                "echo".to_string(),
+                "--model".to_string(),
+                "opus".to_string(),
+            ],
+            ..Default::default()
+        });
+        let (workspace, cmd, _) = resume_command(&model, &pane).unwrap();
+        assert_eq!(workspace, "src/other");
+        assert_eq!(cmd, vec!["echo", "--model", "opus"]);
+
+        // A recorded command that happens to end like the resume args is
+        // kept as it was run.
+        model.registry.insert(PaneRecord {
+            id: "4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10".to_string(),
+            workspace_path: "src/other".to_string(),
+            command: vec!["echo".to_string(), "--continue".to_string()],
+            ..Default::default()
+        });
+        let (_, again, args) = resume_command(&model, &pane).unwrap();
+        assert_eq!(
+            [again, args].concat(),
+            vec!["echo", "--continue", "--continue"]
+        );
+
+        pane.agent_name = "gemini".to_string();
+        assert!(matches!(
+            resume_command(&model, &pane),
+            Err(MaestroError::ResumeNotConfigured(_))
+        ));
+
+        pane.status = PaneStatus::Running;
+        assert!(matches!(
+            resume_command(&model, &pane),
+            Err(MaestroError::PaneStillRunning)
+        ));
+    }
+
+    #[test]
+    fn test_set_pane_tab_updates_pane_and_registry() {
+        let mut model = Model {
+            session_name: Some("work".to_string()),
+            ..Default::default()
+        };
+        model.agent_panes.push(AgentPane {
+            tab_name: "old".to_string(),
+            pending_tab_index: Some(0),
+            pane_id: Some(3),
+            floating: true,
+            pinned: true,
+            ..Default::default()
+        });
+        model.registry.insert(PaneRecord {
+            id: "4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10".to_string(),
+            session: "work".to_string(),
+            pane_id: Some(3),
+            tab_name: "old".to_string(),
+            ..Default::default()
+        });
+
+        set_pane_tab(&mut model, 0, "new");
+
+        let pane = &model.agent_panes[0];
+        assert_eq!(pane.tab_name, "new");
+        assert_eq!(pane.pending_tab_index, None);
+        assert!(!pane.floating && !pane.pinned);
+        assert_eq!(model.registry.records()[0].tab_name, "new");
+    }
+
+    #[test]
+    fn test_is_move_target() {
+        let mut model = Model {
+            tab_names: vec!["main".to_string(), "app".to_string()],
+            move_tab_idx: 1,
+            ..Default::default()
+        };
+        assert!(is_move_target(&model, "app"));
+        assert!(!is_move_target(&model, "main"));
+        // The "new tab" entry past the list is never the pane's own tab.
+        model.move_tab_idx = 2;
+        assert!(!is_move_target(&model, "app"));
+    }
+
+    #[test]
+    fn test_placement_resolution() {
+        let mut floating = crate::test_helpers::create_test_agent("floaty");
+        floating.placement = Some(Placement {
+            kind: PlacementKind::Floating,
+            width: Some("50%".to_string()),
+            ..Default::default()
+        });
+        let mut model = Model {
+            agents: vec![floating, crate::test_helpers::create_test_agent("plain")],
+            ..Default::default()
+        };
+        model.config.placement = Placement::new(PlacementKind::Stacked);
+
+        assert_eq!(
+            placement_for(&model, "floaty › yolo").width.as_deref(),
+            Some("50%")
+        );
+        assert_eq!(placement_for(&model, "plain").kind, PlacementKind::Stacked);
+        assert_eq!(
+            placement_with_kind(&model, "floaty", PlacementKind::Floating)
+                .width
+                .as_deref(),
+            Some("50%")
+        );
+        assert_eq!(
+            placement_with_kind(&model, "floaty", PlacementKind::Stacked),
+            Placement::new(PlacementKind::Stacked)
+        );
+    }
+
     #[test]
     fn test_derive_tab_name_from_workspace_host_only() {
         assert_eq!(derive_tab_name_from_workspace(WASI_HOST_MOUNT), None);
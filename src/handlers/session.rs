@@ -1,10 +1,16 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 use zellij_tile::prelude::*;
 
 use crate::agent::{AgentPane, PaneStatus};
-use crate::model::Model;
-use crate::utils::find_agent_by_command;
+use crate::error::MaestroError;
+use crate::model::{Model, SessionPanes};
+use crate::placement::PlacementKind;
+use crate::registry::pane_uuid;
+
+use super::panes::{CTX_ANCHOR, CTX_PLACEMENT, CTX_TRIAL};
+use crate::utils::{match_agent_command, AgentMatch};
 
 pub fn handle_permission_result(model: &mut Model, status: PermissionStatus) {
     match status {
@@ -50,61 +56,126 @@ pub fn apply_tab_update(model: &mut Model, mut tabs: Vec<TabInfo>) {
     });
 
     model.tab_names = tab_names;
+    model.current_tab = tabs.iter().find(|t| t.active).map(|t| t.name.clone());
     model.clamp_selections();
 }
 
 pub fn apply_pane_update(model: &mut Model, update: PaneManifest) {
-    for (tab_idx, pane_list) in update.panes {
-        let tab_name_from_idx = model.tab_names.get(tab_idx).cloned().unwrap_or_default();
-
-        for pane in pane_list {
-            if let Some(existing) = model
-                .agent_panes
-                .iter_mut()
-                .find(|p| p.pane_id == Some(pane.id))
-            {
-                // Update status only - never touch tab_name for existing matched panes
-                // The tab_name was set when the pane was spawned and should be preserved
-                existing.status = if pane.exited {
-                    PaneStatus::Exited(pane.exit_status)
-                } else {
-                    PaneStatus::Running
-                };
-                continue;
-            }
+    let session = model.session_name.clone().unwrap_or_default();
+    for (tab_idx, pane_list) in &update.panes {
+        let tab_name = model.tab_names.get(*tab_idx).cloned().unwrap_or_default();
+        sync_tab_panes(model, &session, *tab_idx, &tab_name, pane_list);
+    }
+    model.clamp_selections();
+}
 
-            // This is a new pane we haven't seen before (discovered via PaneUpdate)
-            let title = pane.title.clone();
-            let command_hint = pane.terminal_command.as_deref().unwrap_or(&title);
-
-            if !pane.is_plugin {
-                if let Some(agent) = find_agent_by_command(&model.agents, command_hint) {
-                    let agent_name = agent.name.clone();
-                    model.agent_panes.push(AgentPane {
-                        pane_title: title,
-                        tab_name: tab_name_from_idx.clone(),
-                        pending_tab_index: if tab_name_from_idx.is_empty() {
-                            Some(tab_idx)
-                        } else {
-                            None
-                        },
-                        pane_id: Some(pane.id),
-                        workspace_path: String::new(),
-                        agent_name,
-                        status: if pane.exited {
-                            PaneStatus::Exited(pane.exit_status)
-                        } else {
-                            PaneStatus::Running
-                        },
-                    });
-                }
+/// Refresh tracked panes of one tab and start tracking newly seen agent panes.
+fn sync_tab_panes(
+    model: &mut Model,
+    session: &str,
+    tab_idx: usize,
+    tab_name: &str,
+    panes: &[PaneInfo],
+) {
+    if let Some(focused) = panes
+        .iter()
+        .find(|p| p.is_focused && !p.is_plugin && !p.is_floating)
+    {
+        if !tab_name.is_empty() {
+            model.focused_panes.insert(tab_name.to_string(), focused.id);
+        }
+    }
+    for pane in panes {
+        if model.trial_panes.contains_key(&pane.id) {
+            continue;
+        }
+        if let Some(existing) = model.agent_panes.iter_mut().find(|p| {
+            p.pane_id == Some(pane.id) || (p.pane_id.is_none() && p.pane_title == pane.title)
+        }) {
+            // Update status only - never touch tab_name for existing matched panes
+            // The tab_name was set when the pane was spawned and should be preserved
+            existing.pane_id = Some(pane.id);
+            existing.status = pane_status(pane);
+            existing.floating = pane.is_floating;
+            // Only floating panes can be pinned.
+            existing.pinned &= pane.is_floating;
+            continue;
+        }
+
+        if let Some((agent_pane, record)) =
+            identify_pane(model, session, tab_idx, tab_name, pane, &model.agent_panes)
+        {
+            if let Some(record) = record {
+                model.registry.bind_pane_id(&record, session, pane.id);
             }
+            model.agent_panes.push(agent_pane);
         }
     }
-    model.clamp_selections();
+}
+
+/// Work out which agent an untracked pane runs: first by the registry of
+/// spawned panes (pane id, then the recorded command for panes whose id is
+/// not bound yet), then by scoring its command line against the configured
+/// agents. `tracked` are the panes of `session` already identified. Returns
+/// the id of the matching registry record alongside, for the caller to bind
+/// to the pane; nothing is changed here.
+fn identify_pane(
+    model: &Model,
+    session: &str,
+    tab_idx: usize,
+    tab_name: &str,
+    pane: &PaneInfo,
+    tracked: &[AgentPane],
+) -> Option<(AgentPane, Option<String>)> {
+    if pane.is_plugin {
+        return None;
+    }
+    let mut agent_pane = AgentPane {
+        pane_title: pane.title.clone(),
+        tab_name: tab_name.to_string(),
+        pending_tab_index: tab_name.is_empty().then_some(tab_idx),
+        pane_id: Some(pane.id),
+        status: pane_status(pane),
+        floating: pane.is_floating,
+        ..Default::default()
+    };
+
+    let command_hint = pane.terminal_command.as_deref().unwrap_or(&pane.title);
+    let record = model
+        .registry
+        .find_by_pane_id(session, pane.id)
+        .or_else(|| {
+            model.registry.find_by_command(session, command_hint, |id| {
+                tracked.iter().any(|p| p.pane_id == Some(id))
+            })
+        });
+    if let Some(record) = record {
+        agent_pane.agent_name = record.agent_name.clone();
+        agent_pane.workspace_path = record.workspace_path.clone();
+        return Some((agent_pane, Some(record.id.clone())));
+    }
+
+    match match_agent_command(&model.agents, command_hint) {
+        AgentMatch::None => return None,
+        AgentMatch::Unique(name) => agent_pane.agent_name = name,
+        AgentMatch::Ambiguous(names) => agent_pane.candidates = names,
+    }
+    Some((agent_pane, None))
+}
+
+fn pane_status(pane: &PaneInfo) -> PaneStatus {
+    if pane.exited {
+        PaneStatus::Exited(pane.exit_status)
+    } else {
+        PaneStatus::Running
+    }
 }
 
 pub fn handle_command_pane_opened(model: &mut Model, pane_id: u32, ctx: BTreeMap<String, String>) {
+    if let Some(agent) = ctx.get(CTX_TRIAL) {
+        record_trial_pane(model, pane_id, agent.clone());
+        return;
+    }
     let title = ctx
         .get("pane_title")
         .cloned()
@@ -112,6 +183,19 @@ pub fn handle_command_pane_opened(model: &mut Model, pane_id: u32, ctx: BTreeMap
     let workspace_path = ctx.get("cwd").cloned().unwrap_or_default();
     let agent_name = ctx.get("agent").cloned().unwrap_or_default();
 
+    let placement = ctx
+        .get(CTX_PLACEMENT)
+        .and_then(|p| PlacementKind::parse(p).ok());
+    let anchor = ctx.get(CTX_ANCHOR).and_then(|id| id.parse::<u32>().ok());
+    apply_placement(pane_id, placement, anchor);
+
+    // Bind the spawn's UUID to the pane id so the pane is recognised after
+    // the plugin reloads.
+    if let Some(id) = pane_uuid(&title) {
+        let session = model.session_name.clone().unwrap_or_default();
+        model.registry.bind_pane_id(id, &session, pane_id);
+    }
+
     let tab_names_snapshot = model.tab_names.clone();
     let first_tab = tab_names_snapshot.first().cloned();
     let ctx_tab_name = ctx.get("tab_name").cloned();
@@ -138,6 +222,7 @@ pub fn handle_command_pane_opened(model: &mut Model, pane_id: u32, ctx: BTreeMap
         }
         if !agent_name.is_empty() {
             existing.agent_name = agent_name.clone();
+            existing.candidates.clear();
         }
         existing.status = PaneStatus::Running;
     } else {
@@ -152,12 +237,23 @@ pub fn handle_command_pane_opened(model: &mut Model, pane_id: u32, ctx: BTreeMap
             pane_id: Some(pane_id),
             workspace_path,
             agent_name,
+            candidates: Vec::new(),
             status: PaneStatus::Running,
+            floating: placement == Some(PlacementKind::Floating),
+            pinned: false,
         });
     }
     model.clamp_selections();
 }
 
+/// Finish placements Zellij cannot do when opening a pane: stack panes onto
+/// the pane focused at spawn.
+fn apply_placement(pane_id: u32, placement: Option<PlacementKind>, anchor: Option<u32>) {
+    if let (Some(PlacementKind::Stacked), Some(anchor)) = (placement, anchor) {
+        stack_panes(vec![PaneId::Terminal(anchor), PaneId::Terminal(pane_id)]);
+    }
+}
+
 fn rebuild_from_session_infos(model: &mut Model, session_infos: &[SessionInfo]) {
     for session in session_infos {
         let session_name = session.name.clone();
@@ -176,72 +272,8 @@ fn rebuild_from_session_infos(model: &mut Model, session_infos: &[SessionInfo])
         }
 
         for (tab_idx, pane_list) in &session.panes.panes {
-            let tab_name_from_idx = tab_lookup.get(tab_idx).cloned().unwrap_or_default();
-
-            let mut unmatched_in_tab: Vec<usize> = if !tab_name_from_idx.is_empty() {
-                model
-                    .agent_panes
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, p)| p.pane_id.is_none() && p.tab_name == tab_name_from_idx)
-                    .map(|(idx, _)| idx)
-                    .collect()
-            } else {
-                Vec::new()
-            };
-
-            for pane in pane_list {
-                if let Some(existing) = model
-                    .agent_panes
-                    .iter_mut()
-                    .find(|p| p.pane_id == Some(pane.id))
-                {
-                    // Update status only - preserve tab_name
-                    existing.status = if pane.exited {
-                        PaneStatus::Exited(pane.exit_status)
-                    } else {
-                        PaneStatus::Running
-                    };
-                    continue;
-                }
-
-                if let Some(unmatched_idx) = unmatched_in_tab.pop() {
-                    let existing = &mut model.agent_panes[unmatched_idx];
-                    existing.pane_id = Some(pane.id);
-                    existing.status = if pane.exited {
-                        PaneStatus::Exited(pane.exit_status)
-                    } else {
-                        PaneStatus::Running
-                    };
-                    // tab_name already set, don't overwrite
-                    continue;
-                }
-
-                // New pane discovered via session info
-                if !pane.is_plugin {
-                    let command_hint = pane.terminal_command.as_deref().unwrap_or(&pane.title);
-                    if let Some(agent) = find_agent_by_command(&model.agents, command_hint) {
-                        let agent_name = agent.name.clone();
-                        model.agent_panes.push(AgentPane {
-                            pane_title: pane.title.clone(),
-                            tab_name: tab_name_from_idx.clone(),
-                            pending_tab_index: if tab_name_from_idx.is_empty() {
-                                Some(*tab_idx)
-                            } else {
-                                None
-                            },
-                            pane_id: Some(pane.id),
-                            workspace_path: String::new(),
-                            agent_name,
-                            status: if pane.exited {
-                                PaneStatus::Exited(pane.exit_status)
-                            } else {
-                                PaneStatus::Running
-                            },
-                        });
-                    }
-                }
-            }
+            let tab_name = tab_lookup.get(tab_idx).cloned().unwrap_or_default();
+            sync_tab_panes(model, &session_name, *tab_idx, &tab_name, pane_list);
         }
     }
     model.clamp_selections();
@@ -253,6 +285,10 @@ pub fn handle_command_pane_exited(
     exit_status: Option<i32>,
     ctx: BTreeMap<String, String>,
 ) {
+    if let Some(agent) = model.trial_panes.get(&pane_id).cloned() {
+        report_trial_exit(model, agent, exit_status);
+        return;
+    }
     let title = ctx
         .get("pane_title")
         .cloned()
@@ -267,6 +303,26 @@ pub fn handle_command_pane_exited(
     model.clamp_selections();
 }
 
+/// Remember a pane opened by "try agent" so it stays out of the agent pane
+/// list. A pane update may have listed it before the opened event arrived.
+fn record_trial_pane(model: &mut Model, pane_id: u32, agent: String) {
+    model.trial_panes.insert(pane_id, agent);
+    model.agent_panes.retain(|p| p.pane_id != Some(pane_id));
+    model.clamp_selections();
+}
+
+/// Tell the config screen how a tried agent exited.
+fn report_trial_exit(model: &mut Model, agent: String, exit_status: Option<i32>) {
+    match exit_status {
+        Some(0) => {
+            model.notice = format!("{agent} exited successfully");
+            model.clear_error();
+        }
+        Some(code) => model.error_message = MaestroError::TrialFailed(agent, code).to_string(),
+        None => model.error_message = MaestroError::TrialInterrupted(agent).to_string(),
+    }
+}
+
 pub fn handle_command_pane_rerun(model: &mut Model, pane_id: u32, ctx: BTreeMap<String, String>) {
     let title = ctx
         .get("pane_title")
@@ -282,7 +338,11 @@ pub fn handle_command_pane_rerun(model: &mut Model, pane_id: u32, ctx: BTreeMap<
     model.clamp_selections();
 }
 
-pub fn handle_session_update(model: &mut Model, sessions: Vec<SessionInfo>) {
+pub fn handle_session_update(
+    model: &mut Model,
+    sessions: Vec<SessionInfo>,
+    resurrectable: Vec<(String, Duration)>,
+) {
     let current_session_name = sessions
         .iter()
         .find(|s| s.is_current_session)
@@ -303,17 +363,89 @@ pub fn handle_session_update(model: &mut Model, sessions: Vec<SessionInfo>) {
         if let Some(session) = sessions.iter().find(|s| &s.name == session_name) {
             let mut tabs = session.tabs.clone();
             tabs.sort_by_key(|t| t.position);
+            model.current_tab = tabs.iter().find(|t| t.active).map(|t| t.name.clone());
             model.tab_names = tabs.into_iter().map(|t| t.name).collect();
         }
     }
 
     rebuild_from_session_infos(model, &sessions);
+    collect_other_sessions(model, &sessions);
+    apply_resurrectable_sessions(model, &sessions, &resurrectable);
+}
+
+/// Track exited sessions that had agent panes, most recent first, and forget
+/// registry records of sessions that are neither running nor resurrectable.
+fn apply_resurrectable_sessions(
+    model: &mut Model,
+    sessions: &[SessionInfo],
+    resurrectable: &[(String, Duration)],
+) {
+    let live: BTreeSet<&str> = sessions.iter().map(|s| s.name.as_str()).collect();
+    let mut dead: Vec<&(String, Duration)> = resurrectable
+        .iter()
+        .filter(|(name, _)| !live.contains(name.as_str()))
+        .collect();
+    dead.sort_by_key(|(_, age)| *age);
+
+    if !live.is_empty() {
+        model
+            .registry
+            .retain_sessions(|name| live.contains(name) || dead.iter().any(|(n, _)| n == name));
+    }
+    model.resurrectable_sessions = dead
+        .into_iter()
+        .map(|(name, _)| name.clone())
+        .filter(|name| !model.registry.session_records(name).is_empty())
+        .collect();
+    model.clamp_selections();
+}
+
+/// Record the agent panes of every session other than the current one. The
+/// registry is only read here: panes of other sessions are bound by their
+/// own plugin instance.
+fn collect_other_sessions(model: &mut Model, sessions: &[SessionInfo]) {
+    let mut others = Vec::new();
+    for session in sessions {
+        if model.session_name.as_deref() == Some(session.name.as_str()) {
+            continue;
+        }
+        let mut tabs = session.tabs.clone();
+        tabs.sort_by_key(|t| t.position);
+        let tab_names: Vec<String> = tabs.into_iter().map(|t| t.name).collect();
+
+        let mut panes = Vec::new();
+        for (tab_idx, pane_list) in &session.panes.panes {
+            let tab_name = tab_names.get(*tab_idx).cloned().unwrap_or_default();
+            for pane in pane_list {
+                if let Some((agent_pane, _)) =
+                    identify_pane(model, &session.name, *tab_idx, &tab_name, pane, &panes)
+                {
+                    panes.push(agent_pane);
+                }
+            }
+        }
+        if !panes.is_empty() {
+            others.push(SessionPanes {
+                name: session.name.clone(),
+                tab_names,
+                panes,
+            });
+        }
+    }
+    others.sort_by(|a, b| a.name.cmp(&b.name));
+    model.other_sessions = others;
+    model.clamp_selections();
 }
 
 pub fn handle_pane_closed(model: &mut Model, pane_id: PaneId) {
     let pid = match pane_id {
         PaneId::Terminal(id) | PaneId::Plugin(id) => id,
     };
+    if let PaneId::Terminal(id) = pane_id {
+        let session = model.session_name.clone().unwrap_or_default();
+        model.registry.remove_pane(&session, id);
+        model.trial_panes.remove(&id);
+    }
     model.agent_panes.retain(|p| p.pane_id != Some(pid));
     model.clamp_selections();
 }
@@ -321,6 +453,8 @@ pub fn handle_pane_closed(model: &mut Model, pane_id: PaneId) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::registry::PaneRecord;
+    use crate::test_helpers::create_test_agent;
     use std::collections::BTreeMap;
 
     fn make_tab(name: &str, position: usize) -> TabInfo {
@@ -365,7 +499,7 @@ mod tests {
         let session = make_session("s", tabs, true);
         let mut model = Model::default();
 
-        handle_session_update(&mut model, vec![session]);
+        handle_session_update(&mut model, vec![session], Vec::new());
 
         assert_eq!(model.session_name.as_deref(), Some("s"));
         assert_eq!(model.tab_names, vec!["one".to_string(), "two".to_string()]);
@@ -384,12 +518,243 @@ mod tests {
             pane_id: Some(1),
             workspace_path: String::new(),
             agent_name: String::new(),
+            candidates: Vec::new(),
             status: PaneStatus::Running,
+            floating: false,
+            pinned: false,
         });
 
         apply_tab_update(&mut model, vec![make_tab("renamed", 0)]);
 
         assert_eq!(model.agent_panes.len(), 1);
         assert_eq!(model.agent_panes[0].tab_name, "renamed");
+        assert_eq!(model.current_tab.as_deref(), Some("renamed"));
+    }
+
+    fn make_pane(id: u32, title: &str, command: Option<&str>) -> PaneInfo {
+        PaneInfo {
+            id,
+            title: title.to_string(),
+            terminal_command: command.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn manifest(panes: Vec<PaneInfo>) -> PaneManifest {
+        PaneManifest {
+            panes: [(0, panes)].into(),
+        }
+    }
+
+    #[test]
+    fn pane_update_identifies_registered_panes() {
+        let id = "4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10";
+        let mut model = Model {
+            tab_names: vec!["app".to_string()],
+            session_name: Some("work".to_string()),
+            ..Default::default()
+        };
+        model.registry.insert(PaneRecord {
+            id: id.to_string(),
+            session: "work".to_string(),
+            pane_id: Some(4),
+            agent_name: "claude › yolo".to_string(),
+            workspace_path: "src/app".to_string(),
+            ..Default::default()
+        });
+        model.registry.insert(PaneRecord {
+            id: "0b9d9c1e-7c55-4d1e-8f3a-2a6b5c4d3e21".to_string(),
+            session: "work".to_string(),
+            agent_name: "codex".to_string(),
+            command: vec!["codex".to_string(), "--full-auto".to_string()],
+            ..Default::default()
+        });
+        model.registry.take_dirty();
+
+        // The agent set its own title, but the pane id is bound; the layout
+        // pane has no id yet and is found by its command.
+        apply_pane_update(
+            &mut model,
+            manifest(vec![
+                make_pane(4, "✳ Thinking", Some("sh")),
+                make_pane(5, "app", Some("codex --full-auto")),
+            ]),
+        );
+
+        assert_eq!(model.agent_panes.len(), 2);
+        assert_eq!(model.agent_panes[0].agent_name, "claude › yolo");
+        assert_eq!(model.agent_panes[0].workspace_path, "src/app");
+        assert_eq!(model.agent_panes[1].agent_name, "codex");
+        assert!(model.registry.take_dirty());
+        assert_eq!(
+            model
+                .registry
+                .find_by_pane_id("work", 5)
+                .map(|r| r.agent_name.as_str()),
+            Some("codex")
+        );
+    }
+
+    #[test]
+    fn pane_update_reports_ambiguous_commands() {
+        let mut model = Model {
+            tab_names: vec!["main".to_string()],
+            ..Default::default()
+        };
+        model.agents = vec![create_test_agent("one"), create_test_agent("two")];
+        model.agents[1].args = model.agents[0].args.clone();
+
+        apply_pane_update(
+            &mut model,
+            manifest(vec![
+                make_pane(1, "Pane #1", Some("echo one")),
+                make_pane(2, "Pane #2", Some("bash")),
+            ]),
+        );
+
+        assert_eq!(model.agent_panes.len(), 1);
+        assert!(model.agent_panes[0].agent_name.is_empty());
+        assert_eq!(model.agent_panes[0].candidates, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn pane_update_tracks_floating_state() {
+        let mut model = Model {
+            agents: vec![create_test_agent("one")],
+            tab_names: vec!["main".to_string()],
+            ..Default::default()
+        };
+        let mut pane = make_pane(1, "Pane #1", Some("echo one"));
+        pane.is_floating = true;
+        apply_pane_update(&mut model, manifest(vec![pane.clone()]));
+        assert!(model.agent_panes[0].floating);
+
+        model.agent_panes[0].pinned = true;
+        pane.is_floating = false;
+        apply_pane_update(&mut model, manifest(vec![pane]));
+        assert!(!model.agent_panes[0].floating);
+        assert!(!model.agent_panes[0].pinned);
+    }
+
+    #[test]
+    fn trial_panes_are_not_tracked_as_agent_panes() {
+        let mut model = Model {
+            agents: vec![create_test_agent("one")],
+            tab_names: vec!["main".to_string()],
+            ..Default::default()
+        };
+        let pane = make_pane(7, "Pane #7", Some("echo one"));
+        // The pane update can arrive before the opened event.
+        apply_pane_update(&mut model, manifest(vec![pane.clone()]));
+        assert_eq!(model.agent_panes.len(), 1);
+
+        record_trial_pane(&mut model, 7, "one".to_string());
+        assert!(model.agent_panes.is_empty());
+        assert_eq!(model.trial_panes.get(&7).map(String::as_str), Some("one"));
+
+        apply_pane_update(&mut model, manifest(vec![pane]));
+        assert!(model.agent_panes.is_empty());
+    }
+
+    #[test]
+    fn trial_exit_status_is_reported() {
+        let mut model = Model::default();
+        model.trial_panes.insert(3, "one".to_string());
+        model.trial_panes.insert(4, "two".to_string());
+
+        handle_command_pane_exited(&mut model, 3, Some(0), BTreeMap::new());
+        assert_eq!(model.notice, "one exited successfully");
+        assert!(model.error_message.is_empty());
+
+        handle_command_pane_exited(&mut model, 4, Some(127), BTreeMap::new());
+        assert_eq!(model.error_message, "two exited with status 127");
+        assert!(model.agent_panes.is_empty());
+    }
+
+    #[test]
+    fn session_update_collects_other_sessions() {
+        let mut model = Model {
+            agents: vec![create_test_agent("one")],
+            ..Default::default()
+        };
+
+        let current = make_session("here", vec![make_tab("main", 0)], true);
+        let mut other = make_session("there", vec![make_tab("b", 1), make_tab("a", 0)], false);
+        other.panes = PaneManifest {
+            panes: [
+                (1, vec![make_pane(5, "Pane #1", Some("echo one"))]),
+                (0, vec![make_pane(6, "Pane #2", Some("bash"))]),
+            ]
+            .into(),
+        };
+        let idle = make_session("idle", Vec::new(), false);
+
+        handle_session_update(&mut model, vec![other, current, idle], Vec::new());
+
+        assert!(model.agent_panes.is_empty());
+        assert_eq!(model.other_sessions.len(), 1);
+        let there = &model.other_sessions[0];
+        assert_eq!(there.name, "there");
+        assert_eq!(there.tab_names, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(there.panes.len(), 1);
+        assert_eq!(there.panes[0].tab_name, "b");
+        assert_eq!(there.panes[0].agent_name, "one");
+    }
+
+    #[test]
+    fn session_update_leaves_other_sessions_registry_alone() {
+        let mut model = Model {
+            session_name: Some("here".to_string()),
+            ..Default::default()
+        };
+        model.registry.insert(PaneRecord {
+            id: "0b9d9c1e-7c55-4d1e-8f3a-2a6b5c4d3e21".to_string(),
+            session: "there".to_string(),
+            agent_name: "codex".to_string(),
+            command: vec!["codex".to_string()],
+            ..Default::default()
+        });
+        model.registry.take_dirty();
+
+        let current = make_session("here", vec![make_tab("main", 0)], true);
+        let mut other = make_session("there", vec![make_tab("a", 0)], false);
+        other.panes = PaneManifest {
+            panes: [(0, vec![make_pane(7, "Pane #1", Some("codex"))])].into(),
+        };
+        handle_session_update(&mut model, vec![current, other], Vec::new());
+
+        assert_eq!(model.other_sessions[0].panes[0].agent_name, "codex");
+        assert!(model.registry.find_by_pane_id("there", 7).is_none());
+        assert!(!model.registry.take_dirty());
+    }
+
+    #[test]
+    fn session_update_lists_resurrectable_sessions_with_agents() {
+        let mut model = Model::default();
+        for (id, session) in [
+            ("4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10", "old"),
+            ("0b9d9c1e-7c55-4d1e-8f3a-2a6b5c4d3e21", "recent"),
+            ("9a1f7e52-3b0c-4d8e-a6f2-5c9b1d3e7f40", "deleted"),
+        ] {
+            model.registry.insert(PaneRecord {
+                id: id.to_string(),
+                session: session.to_string(),
+                agent_name: "claude".to_string(),
+                ..Default::default()
+            });
+        }
+
+        handle_session_update(
+            &mut model,
+            vec![make_session("here", vec![make_tab("main", 0)], true)],
+            vec![
+                ("old".to_string(), Duration::from_secs(3600)),
+                ("recent".to_string(), Duration::from_secs(60)),
+                ("empty".to_string(), Duration::from_secs(1)),
+            ],
+        );
+
+        assert_eq!(model.resurrectable_sessions, vec!["recent", "old"]);
+        assert!(model.registry.session_records("deleted").is_empty());
     }
 }
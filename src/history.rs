@@ -0,0 +1,281 @@
+//! Recently used workspaces, ranked by frecency.
+//!
+//! Every workspace an agent is spawned in is recorded with a use count and
+//! the time of its last use. The new-pane wizard offers them, most
+//! frequently and recently used first, before anything is typed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use kdl::{KdlDocument, KdlNode};
+
+use crate::error::{MaestroError, MaestroResult};
+
+/// Oldest entries beyond this many are forgotten.
+const MAX_ENTRIES: usize = 100;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const MONTH: u64 = 30 * DAY;
+
+/// A workspace that was spawned into.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WorkspaceVisit {
+    /// Workspace as entered in the wizard (e.g. `/host/src/app`).
+    pub path: String,
+    pub count: u32,
+    /// Unix timestamp of the last use.
+    pub last_used: u64,
+}
+
+impl WorkspaceVisit {
+    /// Use count weighted by how recently the workspace was used.
+    pub fn frecency(&self, now: u64) -> u64 {
+        let age = now.saturating_sub(self.last_used);
+        let weight = match age {
+            a if a < HOUR => 16,
+            a if a < DAY => 8,
+            a if a < WEEK => 4,
+            a if a < MONTH => 2,
+            _ => 1,
+        };
+        u64::from(self.count) * weight
+    }
+}
+
+/// Workspace usage history, persisted next to `agents.kdl`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WorkspaceHistory {
+    visits: Vec<WorkspaceVisit>,
+    dirty: bool,
+}
+
+impl WorkspaceHistory {
+    pub fn visits(&self) -> &[WorkspaceVisit] {
+        &self.visits
+    }
+
+    /// Count a use of `path` at `now`.
+    pub fn record(&mut self, path: &str, now: u64) {
+        let path = normalize(path);
+        if path.is_empty() {
+            return;
+        }
+        match self.visits.iter_mut().find(|v| v.path == path) {
+            Some(visit) => {
+                visit.count = visit.count.saturating_add(1);
+                visit.last_used = now;
+            }
+            None => self.visits.push(WorkspaceVisit {
+                path,
+                count: 1,
+                last_used: now,
+            }),
+        }
+        if self.visits.len() > MAX_ENTRIES {
+            self.visits.sort_by_key(|v| std::cmp::Reverse(v.last_used));
+            self.visits.truncate(MAX_ENTRIES);
+        }
+        self.dirty = true;
+    }
+
+    /// Forget `path`. Returns whether it was recorded.
+    pub fn remove(&mut self, path: &str) -> bool {
+        let path = normalize(path);
+        let before = self.visits.len();
+        self.visits.retain(|v| v.path != path);
+        let removed = self.visits.len() != before;
+        self.dirty |= removed;
+        removed
+    }
+
+    /// Recorded workspaces, highest frecency first, most recent on ties.
+    pub fn ranked(&self, now: u64) -> Vec<String> {
+        let mut visits: Vec<&WorkspaceVisit> = self.visits.iter().collect();
+        visits.sort_by(|a, b| {
+            b.frecency(now)
+                .cmp(&a.frecency(now))
+                .then_with(|| b.last_used.cmp(&a.last_used))
+        });
+        visits.into_iter().map(|v| v.path.clone()).collect()
+    }
+
+    /// Whether the history changed since the last call.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+fn normalize(path: &str) -> String {
+    let trimmed = path.trim();
+    match trimmed.trim_end_matches('/') {
+        "" => trimmed.to_string(),
+        stripped => stripped.to_string(),
+    }
+}
+
+/// Current Unix time in seconds.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Get the default history path (`~/.config/maestro/workspaces.kdl`).
+pub fn default_history_path() -> PathBuf {
+    crate::agent::config_base_dir().join("workspaces.kdl")
+}
+
+/// Load the workspace history, treating a missing file as empty.
+pub fn load_history(path: &Path) -> MaestroResult<WorkspaceHistory> {
+    let data = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(WorkspaceHistory::default())
+        }
+        Err(e) => {
+            return Err(MaestroError::FileRead {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })
+        }
+    };
+    parse_history_kdl(&data)
+}
+
+/// Save the workspace history, creating the config directory if needed.
+pub fn save_history(path: &Path, history: &WorkspaceHistory) -> MaestroResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| MaestroError::DirectoryCreate {
+            path: parent.to_path_buf(),
+            message: e.to_string(),
+        })?;
+    }
+    fs::write(path, history_to_kdl(history).as_bytes()).map_err(|e| MaestroError::FileWrite {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+fn parse_history_kdl(data: &str) -> MaestroResult<WorkspaceHistory> {
+    let doc: KdlDocument = data
+        .parse()
+        .map_err(|e: kdl::KdlError| MaestroError::ConfigParse(e.to_string()))?;
+    let mut history = WorkspaceHistory::default();
+    for node in doc
+        .nodes()
+        .iter()
+        .filter(|n| n.name().value() == "workspace")
+    {
+        let Some(path) = node.get("path").and_then(|e| e.value().as_string()) else {
+            continue;
+        };
+        let number = |key: &str| node.get(key).and_then(|e| e.value().as_i64());
+        history.visits.push(WorkspaceVisit {
+            path: path.to_string(),
+            count: number("count")
+                .and_then(|c| u32::try_from(c).ok())
+                .unwrap_or(1),
+            last_used: number("last")
+                .and_then(|t| u64::try_from(t).ok())
+                .unwrap_or(0),
+        });
+    }
+    Ok(history)
+}
+
+fn history_to_kdl(history: &WorkspaceHistory) -> String {
+    let mut doc = KdlDocument::new();
+    for visit in &history.visits {
+        let mut node = KdlNode::new("workspace");
+        node.insert("path", visit.path.clone());
+        node.insert("count", i64::from(visit.count));
+        node.insert("last", i64::try_from(visit.last_used).unwrap_or(i64::MAX));
+        doc.nodes_mut().push(node);
+    }
+    doc.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn test_record_counts_and_normalizes() {
+        let mut history = WorkspaceHistory::default();
+        history.record("/host/src/app/", NOW - DAY);
+        history.record("/host/src/app", NOW);
+        history.record("   ", NOW);
+
+        assert_eq!(history.visits().len(), 1);
+        assert_eq!(history.visits()[0].count, 2);
+        assert_eq!(history.visits()[0].last_used, NOW);
+        assert!(history.take_dirty());
+        assert!(!history.take_dirty());
+    }
+
+    #[test]
+    fn test_ranked_by_frecency() {
+        let mut history = WorkspaceHistory::default();
+        // Used often, but long ago.
+        for _ in 0..5 {
+            history.record("old", NOW - 2 * MONTH);
+        }
+        // Used once, just now.
+        history.record("fresh", NOW);
+        // Used three times this week.
+        for _ in 0..3 {
+            history.record("weekly", NOW - 2 * DAY);
+        }
+
+        assert_eq!(history.ranked(NOW), vec!["fresh", "weekly", "old"]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut history = WorkspaceHistory::default();
+        history.record("a", NOW);
+        history.take_dirty();
+
+        assert!(!history.remove("b"));
+        assert!(!history.take_dirty());
+        assert!(history.remove("a/"));
+        assert!(history.take_dirty());
+        assert!(history.visits().is_empty());
+    }
+
+    #[test]
+    fn test_record_bounds_entries() {
+        let mut history = WorkspaceHistory::default();
+        for i in 0..(MAX_ENTRIES as u64 + 5) {
+            history.record(&format!("ws{i}"), NOW + i);
+        }
+        assert_eq!(history.visits().len(), MAX_ENTRIES);
+        assert!(!history.visits().iter().any(|v| v.path == "ws0"));
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut history = WorkspaceHistory::default();
+        history.record("/host/src/app", NOW);
+        history.record("/host/src/app", NOW);
+        history.record("/host/src/api", NOW - HOUR);
+
+        save_history(temp_file.path(), &history).unwrap();
+        let loaded = load_history(temp_file.path()).unwrap();
+        assert_eq!(loaded.visits(), history.visits());
+    }
+
+    #[test]
+    fn test_load_history_missing_file() {
+        let history = load_history(Path::new("/nonexistent/maestro/workspaces.kdl")).unwrap();
+        assert!(history.visits().is_empty());
+    }
+}
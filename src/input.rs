@@ -0,0 +1,370 @@
+//! Single-line text input with a cursor.
+//!
+//! Every text field of the plugin (workspace path, tab name, prompted values,
+//! agent form fields, import/export path) is a [`TextInput`], so they share
+//! the same editing keys:
+//!
+//! | Key                         | Action                       |
+//! |-----------------------------|------------------------------|
+//! | `←`/`→`                     | Move one character           |
+//! | `Alt-b`/`Alt-f`, `Ctrl-←/→` | Move one word                |
+//! | `Home`/`End`, `Ctrl-a/e`    | Move to start/end            |
+//! | `Backspace`/`Delete`        | Delete before/under cursor   |
+//! | `Ctrl-w`, `Alt-Backspace`   | Delete word before cursor    |
+//! | `Alt-d`                     | Delete word after cursor     |
+//! | `Ctrl-u`                    | Delete to start              |
+
+use std::fmt;
+use std::ops::Deref;
+
+use zellij_tile::prelude::{BareKey, KeyModifier, KeyWithModifier};
+
+/// Shown in the cell after the last character when the cursor is there.
+const END_CURSOR: char = '_';
+
+/// Editable text and a cursor position, in chars.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    /// Cursor position in chars, `0..=len`.
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replace the text, moving the cursor to its end.
+    pub fn set(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    /// Byte offset of char index `idx`.
+    fn byte_at(&self, idx: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(idx)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let at = self.byte_at(self.cursor);
+        self.value.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Insert pasted text at the cursor. Line breaks and other control
+    /// characters become spaces; a trailing line break is dropped.
+    pub fn insert_str(&mut self, text: &str) {
+        let cleaned: String = text
+            .trim_end_matches(['\r', '\n'])
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        let at = self.byte_at(self.cursor);
+        self.value.insert_str(at, &cleaned);
+        self.cursor += cleaned.chars().count();
+    }
+
+    /// Remove the chars in `from..to` and put the cursor at `from`.
+    fn delete_range(&mut self, from: usize, to: usize) {
+        let (start, end) = (self.byte_at(from), self.byte_at(to));
+        self.value.replace_range(start..end, "");
+        self.cursor = from;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.delete_range(self.cursor - 1, self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            self.delete_range(self.cursor, self.cursor + 1);
+        }
+    }
+
+    pub fn delete_word_back(&mut self) {
+        let start = self.word_start();
+        self.delete_range(start, self.cursor);
+    }
+
+    pub fn delete_word_forward(&mut self) {
+        let (cursor, end) = (self.cursor, self.word_end());
+        self.delete_range(cursor, end);
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.delete_range(0, self.cursor);
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start();
+    }
+
+    pub fn move_word_right(&mut self) {
+        self.cursor = self.word_end();
+    }
+
+    /// Start of the word before the cursor, skipping separators first.
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut idx = self.cursor;
+        while idx > 0 && is_separator(chars[idx - 1]) {
+            idx -= 1;
+        }
+        while idx > 0 && !is_separator(chars[idx - 1]) {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// End of the word after the cursor, skipping separators first.
+    fn word_end(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut idx = self.cursor;
+        while idx < chars.len() && is_separator(chars[idx]) {
+            idx += 1;
+        }
+        while idx < chars.len() && !is_separator(chars[idx]) {
+            idx += 1;
+        }
+        idx
+    }
+
+    /// Apply an editing key. Returns `false` for keys that are not editing
+    /// keys, which the caller handles instead.
+    pub fn handle_key(&mut self, key: &KeyWithModifier) -> bool {
+        let ctrl = key.key_modifiers.contains(&KeyModifier::Ctrl);
+        let alt = key.key_modifiers.contains(&KeyModifier::Alt);
+        match key.bare_key {
+            BareKey::Char('w') if ctrl => self.delete_word_back(),
+            BareKey::Char('u') if ctrl => self.delete_to_start(),
+            BareKey::Char('a') if ctrl => self.move_home(),
+            BareKey::Char('e') if ctrl => self.move_end(),
+            BareKey::Char('b') if alt => self.move_word_left(),
+            BareKey::Char('f') if alt => self.move_word_right(),
+            BareKey::Char('d') if alt => self.delete_word_forward(),
+            BareKey::Char(c) if !ctrl && !alt => self.insert_char(c),
+            BareKey::Backspace if alt || ctrl => self.delete_word_back(),
+            BareKey::Backspace => self.backspace(),
+            BareKey::Delete => self.delete(),
+            BareKey::Left if ctrl || alt => self.move_word_left(),
+            BareKey::Right if ctrl || alt => self.move_word_right(),
+            BareKey::Left => self.move_left(),
+            BareKey::Right => self.move_right(),
+            BareKey::Home => self.move_home(),
+            BareKey::End => self.move_end(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// This input without a leading `prefix`, unless the cursor is inside it.
+    pub fn strip_prefix(&self, prefix: &str) -> Option<TextInput> {
+        let rest = self.value.strip_prefix(prefix)?;
+        let skipped = prefix.chars().count();
+        (self.cursor >= skipped).then(|| TextInput {
+            value: rest.to_string(),
+            cursor: self.cursor - skipped,
+        })
+    }
+
+    /// The part of the text that fits in `width` columns, scrolled so the
+    /// cursor stays visible, and the cursor's column in it. When the cursor
+    /// is past the last character its cell shows `_`.
+    pub fn view(&self, width: usize) -> (String, usize) {
+        let width = width.max(1);
+        let start = (self.cursor + 1).saturating_sub(width);
+        let mut shown: String = self.value.chars().skip(start).take(width).collect();
+        if self.cursor == self.len() {
+            shown.push(END_CURSOR);
+        }
+        (shown, self.cursor - start)
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == '/'
+}
+
+impl Deref for TextInput {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for TextInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl PartialEq<str> for TextInput {
+    fn eq(&self, other: &str) -> bool {
+        self.value == other
+    }
+}
+
+impl PartialEq<&str> for TextInput {
+    fn eq(&self, other: &&str) -> bool {
+        self.value == *other
+    }
+}
+
+impl From<String> for TextInput {
+    fn from(value: String) -> Self {
+        let cursor = value.chars().count();
+        Self { value, cursor }
+    }
+}
+
+impl From<&str> for TextInput {
+    fn from(value: &str) -> Self {
+        Self::from(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(bare_key: BareKey, modifiers: &[KeyModifier]) -> KeyWithModifier {
+        let mut key = KeyWithModifier::new(bare_key);
+        for modifier in modifiers {
+            key.key_modifiers.insert(*modifier);
+        }
+        key
+    }
+
+    #[test]
+    fn test_insert_and_move() {
+        let mut input = TextInput::from("clude");
+        input.move_home();
+        input.move_right();
+        input.move_right();
+        input.insert_char('a');
+        assert_eq!(input.value(), "claude");
+        assert_eq!(input.cursor(), 3);
+
+        input.move_end();
+        input.backspace();
+        input.move_home();
+        input.delete();
+        assert_eq!(input.value(), "laud");
+        assert_eq!(input.cursor(), 0);
+    }
+
+    #[test]
+    fn test_multibyte_chars() {
+        let mut input = TextInput::from("héllo");
+        input.move_left();
+        input.move_left();
+        input.move_left();
+        input.backspace();
+        assert_eq!(input.value(), "hllo");
+        input.insert_char('ë');
+        assert_eq!(input.value(), "hëllo");
+    }
+
+    #[test]
+    fn test_word_motion_and_deletion() {
+        let mut input = TextInput::from("--model opus --dir /src/app");
+        input.delete_word_back();
+        assert_eq!(input.value(), "--model opus --dir /src/");
+        input.move_word_left();
+        input.move_word_left();
+        assert_eq!(input.cursor(), 13);
+        input.move_word_right();
+        assert_eq!(input.cursor(), 18);
+        input.move_home();
+        input.delete_word_forward();
+        assert_eq!(input.value(), " opus --dir /src/");
+    }
+
+    #[test]
+    fn test_insert_str_sanitizes_paste() {
+        let mut input = TextInput::from("--dir ");
+        input.insert_str("/src/my\tapp\n");
+        assert_eq!(input.value(), "--dir /src/my app");
+        assert_eq!(input.cursor(), input.value().chars().count());
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut input = TextInput::default();
+        assert!(input.handle_key(&key(BareKey::Char('a'), &[])));
+        assert!(input.handle_key(&key(BareKey::Char('B'), &[KeyModifier::Shift])));
+        assert!(input.handle_key(&key(BareKey::Char(' '), &[])));
+        assert!(input.handle_key(&key(BareKey::Char('c'), &[])));
+        assert_eq!(input.value(), "aB c");
+
+        assert!(input.handle_key(&key(BareKey::Char('w'), &[KeyModifier::Ctrl])));
+        assert_eq!(input.value(), "aB ");
+        assert!(input.handle_key(&key(BareKey::Home, &[])));
+        assert_eq!(input.cursor(), 0);
+
+        assert!(!input.handle_key(&key(BareKey::Char('r'), &[KeyModifier::Ctrl])));
+        assert!(!input.handle_key(&key(BareKey::Enter, &[])));
+        assert!(!input.handle_key(&key(BareKey::Up, &[])));
+        assert_eq!(input.value(), "aB ");
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        let mut input = TextInput::from("/host/src");
+        let stripped = input.strip_prefix("/host/").unwrap();
+        assert_eq!(stripped.value(), "src");
+        assert_eq!(stripped.cursor(), 3);
+
+        input.move_home();
+        assert_eq!(input.strip_prefix("/host/"), None);
+        assert_eq!(input.strip_prefix("/opt/"), None);
+    }
+
+    #[test]
+    fn test_view_scrolls_to_cursor() {
+        let mut input = TextInput::from("abcdefgh");
+        assert_eq!(input.view(20), ("abcdefgh_".to_string(), 8));
+        assert_eq!(input.view(4), ("fgh_".to_string(), 3));
+
+        input.move_home();
+        assert_eq!(input.view(4), ("abcd".to_string(), 0));
+        input.move_word_right();
+        input.move_left();
+        assert_eq!(input.view(4), ("efgh".to_string(), 3));
+    }
+}
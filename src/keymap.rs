@@ -0,0 +1,854 @@
+//! Key bindings: the actions each screen offers and the keys bound to them.
+//!
+//! Every action has default keys that can be replaced from the plugin
+//! configuration with `key_<action> "<key>, <key>"`, using Zellij's key
+//! syntax (`j`, `Down`, `Ctrl n`, `Shift Tab`). An empty value unbinds the
+//! action. The key handlers, the status line hints and the help screen all
+//! read the tables below, so they always show the keys that actually work.
+//! A configured key that another action of the same screen already uses is
+//! reported, and the configured action keeps its defaults.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use zellij_tile::prelude::{BareKey, KeyModifier, KeyWithModifier};
+
+use crate::error::MaestroError;
+use crate::ui::{screen_name, Mode};
+
+/// Something a key can do. The same action is reused on every screen where
+/// it means the same thing (e.g. [`Action::Down`] moves any selection).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Down,
+    Up,
+    Confirm,
+    Back,
+    Yes,
+    No,
+    KillPane,
+    ResumePane,
+    MovePane,
+    ToggleFloating,
+    TogglePinned,
+    NewPane,
+    AllSessions,
+    AgentConfig,
+    RelaunchSession,
+    MoveAgentDown,
+    MoveAgentUp,
+    AddAgent,
+    CloneAgent,
+    TryAgent,
+    EditAgent,
+    DeleteAgent,
+    ToggleHidden,
+    MarkAgent,
+    ImportAgents,
+    ExportAgents,
+    AcceptSuggestion,
+    ToggleHiddenDirs,
+    ForgetWorkspace,
+    RescanProjects,
+    CyclePlacement,
+    NextChoice,
+    NextField,
+    PrevField,
+    CycleConflict,
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 36] = [
+        Action::Down,
+        Action::Up,
+        Action::Confirm,
+        Action::Back,
+        Action::Yes,
+        Action::No,
+        Action::KillPane,
+        Action::ResumePane,
+        Action::MovePane,
+        Action::ToggleFloating,
+        Action::TogglePinned,
+        Action::NewPane,
+        Action::AllSessions,
+        Action::AgentConfig,
+        Action::RelaunchSession,
+        Action::MoveAgentDown,
+        Action::MoveAgentUp,
+        Action::AddAgent,
+        Action::CloneAgent,
+        Action::TryAgent,
+        Action::EditAgent,
+        Action::DeleteAgent,
+        Action::ToggleHidden,
+        Action::MarkAgent,
+        Action::ImportAgents,
+        Action::ExportAgents,
+        Action::AcceptSuggestion,
+        Action::ToggleHiddenDirs,
+        Action::ForgetWorkspace,
+        Action::RescanProjects,
+        Action::CyclePlacement,
+        Action::NextChoice,
+        Action::NextField,
+        Action::PrevField,
+        Action::CycleConflict,
+        Action::Help,
+    ];
+
+    /// Name used in the `key_<name>` configuration entry.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::Yes => "yes",
+            Action::No => "no",
+            Action::KillPane => "kill_pane",
+            Action::ResumePane => "resume_pane",
+            Action::MovePane => "move_pane",
+            Action::ToggleFloating => "toggle_floating",
+            Action::TogglePinned => "toggle_pinned",
+            Action::NewPane => "new_pane",
+            Action::AllSessions => "all_sessions",
+            Action::AgentConfig => "agent_config",
+            Action::RelaunchSession => "relaunch_session",
+            Action::MoveAgentDown => "move_agent_down",
+            Action::MoveAgentUp => "move_agent_up",
+            Action::AddAgent => "add_agent",
+            Action::CloneAgent => "clone_agent",
+            Action::TryAgent => "try_agent",
+            Action::EditAgent => "edit_agent",
+            Action::DeleteAgent => "delete_agent",
+            Action::ToggleHidden => "toggle_hidden",
+            Action::MarkAgent => "mark_agent",
+            Action::ImportAgents => "import_agents",
+            Action::ExportAgents => "export_agents",
+            Action::AcceptSuggestion => "accept_suggestion",
+            Action::ToggleHiddenDirs => "toggle_hidden_dirs",
+            Action::ForgetWorkspace => "forget_workspace",
+            Action::RescanProjects => "rescan_projects",
+            Action::CyclePlacement => "cycle_placement",
+            Action::NextChoice => "next_choice",
+            Action::NextField => "next_field",
+            Action::PrevField => "prev_field",
+            Action::CycleConflict => "cycle_conflict",
+            Action::Help => "help",
+        }
+    }
+
+    /// Keys bound to the action unless the configuration overrides them.
+    pub fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Down => &["j", "Down", "Ctrl n"],
+            Action::Up => &["k", "Up", "Ctrl p"],
+            Action::Confirm => &["Enter"],
+            Action::Back => &["Esc"],
+            Action::Yes => &["y"],
+            Action::No => &["n"],
+            Action::KillPane => &["d"],
+            Action::ResumePane => &["r"],
+            Action::MovePane => &["m"],
+            Action::ToggleFloating => &["f"],
+            Action::TogglePinned => &["p"],
+            Action::NewPane => &["n"],
+            Action::AllSessions => &["s"],
+            Action::AgentConfig => &["c"],
+            Action::RelaunchSession => &["r"],
+            Action::MoveAgentDown => &["J"],
+            Action::MoveAgentUp => &["K"],
+            Action::AddAgent => &["a"],
+            Action::CloneAgent => &["c"],
+            Action::TryAgent => &["t"],
+            Action::EditAgent => &["e"],
+            Action::DeleteAgent => &["d"],
+            Action::ToggleHidden => &["h"],
+            Action::MarkAgent => &["Space"],
+            Action::ImportAgents => &["i"],
+            Action::ExportAgents => &["x"],
+            Action::AcceptSuggestion => &["Tab"],
+            Action::ToggleHiddenDirs => &["Ctrl h"],
+            Action::ForgetWorkspace => &["Ctrl d"],
+            Action::RescanProjects => &["Ctrl r"],
+            Action::CyclePlacement => &["Tab"],
+            Action::NextChoice => &["Tab"],
+            Action::NextField => &["Tab"],
+            Action::PrevField => &["Shift Tab"],
+            Action::CycleConflict => &["Tab"],
+            // `?` is typed text in inputs, where F1 opens the help instead.
+            Action::Help => &["?", "F1"],
+        }
+    }
+}
+
+/// An action offered on a screen: the word the status line uses for it and
+/// the sentence the help screen describes it with. Consecutive entries with
+/// the same label share one hint (`j/k move`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeAction {
+    pub action: Action,
+    pub label: &'static str,
+    pub help: &'static str,
+}
+
+const fn offer(action: Action, label: &'static str, help: &'static str) -> ModeAction {
+    ModeAction {
+        action,
+        label,
+        help,
+    }
+}
+
+/// Listed first on every screen so the hint survives truncation.
+const HELP: ModeAction = offer(Action::Help, "help", "Show the keys of this screen");
+
+const VIEW_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "move", "Select the next pane"),
+    offer(Action::Up, "move", "Select the previous pane"),
+    offer(
+        Action::Confirm,
+        "focus",
+        "Focus the selected pane and close Maestro",
+    ),
+    offer(Action::KillPane, "kill", "Close the selected pane"),
+    offer(
+        Action::ResumePane,
+        "resume",
+        "Respawn an exited pane with its resume arguments",
+    ),
+    offer(
+        Action::MovePane,
+        "move",
+        "Move the selected pane to another tab",
+    ),
+    offer(
+        Action::ToggleFloating,
+        "float",
+        "Toggle the selected pane floating or embedded",
+    ),
+    offer(
+        Action::TogglePinned,
+        "pin",
+        "Pin or unpin the selected floating pane",
+    ),
+    offer(Action::NewPane, "new", "Start a new agent pane"),
+    offer(
+        Action::AllSessions,
+        "sessions",
+        "List agent panes of all sessions",
+    ),
+    offer(Action::AgentConfig, "config", "Manage agents"),
+    offer(Action::Back, "close", "Close Maestro"),
+];
+
+const MOVE_PANE_TAB_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "move", "Select the next tab"),
+    offer(Action::Up, "move", "Select the previous tab"),
+    offer(
+        Action::Confirm,
+        "move here",
+        "Move the pane to the selected tab",
+    ),
+    offer(Action::Back, "cancel", "Back to the pane list"),
+];
+
+const ALL_SESSIONS_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "move", "Select the next pane or session"),
+    offer(Action::Up, "move", "Select the previous pane or session"),
+    offer(
+        Action::Confirm,
+        "switch & focus / resurrect",
+        "Switch to the session and focus the pane, or resurrect an exited session",
+    ),
+    offer(
+        Action::RelaunchSession,
+        "relaunch agents here",
+        "Relaunch an exited session's agents in this session",
+    ),
+    offer(Action::Back, "back", "Back to the pane list"),
+];
+
+const AGENT_CONFIG_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "move", "Select the next agent"),
+    offer(Action::Up, "move", "Select the previous agent"),
+    offer(
+        Action::MoveAgentDown,
+        "reorder",
+        "Move the agent down the list",
+    ),
+    offer(Action::MoveAgentUp, "reorder", "Move the agent up the list"),
+    offer(Action::AddAgent, "add", "Add an agent"),
+    offer(
+        Action::CloneAgent,
+        "clone",
+        "Add a copy of the selected agent",
+    ),
+    offer(
+        Action::TryAgent,
+        "try",
+        "Run the agent in a temporary floating pane",
+    ),
+    offer(Action::EditAgent, "edit", "Edit the selected agent"),
+    offer(Action::DeleteAgent, "delete", "Delete the selected agent"),
+    offer(
+        Action::ToggleHidden,
+        "hide/show",
+        "Hide or show the agent in the new-pane picker",
+    ),
+    offer(Action::MarkAgent, "mark", "Mark the agent for export"),
+    offer(Action::ImportAgents, "import", "Import agents from a file"),
+    offer(
+        Action::ExportAgents,
+        "export",
+        "Export the marked (or selected) agents",
+    ),
+    offer(Action::Back, "back", "Back to the pane list"),
+];
+
+const NEW_PANE_WORKSPACE_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "select", "Select the next suggestion"),
+    offer(Action::Up, "select", "Select the previous suggestion"),
+    offer(
+        Action::AcceptSuggestion,
+        "accept",
+        "Complete the input with the suggestion",
+    ),
+    offer(
+        Action::ToggleHiddenDirs,
+        "hidden",
+        "Show or hide hidden directories",
+    ),
+    offer(
+        Action::ForgetWorkspace,
+        "forget",
+        "Forget the selected recent workspace",
+    ),
+    offer(
+        Action::RescanProjects,
+        "rescan projects",
+        "Rescan the project roots",
+    ),
+    offer(
+        Action::Confirm,
+        "continue",
+        "Use the selected or typed workspace",
+    ),
+    offer(Action::Back, "cancel", "Cancel the new pane"),
+];
+
+const NEW_PANE_TAB_SELECT_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "move", "Select the next tab"),
+    offer(Action::Up, "move", "Select the previous tab"),
+    offer(
+        Action::Confirm,
+        "select",
+        "Open the pane in the selected or typed tab",
+    ),
+    offer(Action::Back, "cancel", "Cancel the new pane"),
+];
+
+const NEW_PANE_AGENT_SELECT_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Down, "move", "Select the next agent"),
+    offer(Action::Up, "move", "Select the previous agent"),
+    offer(
+        Action::CyclePlacement,
+        "placement",
+        "Cycle the pane placement",
+    ),
+    offer(Action::Confirm, "select", "Spawn the selected agent"),
+    offer(Action::Back, "cancel", "Cancel the new pane"),
+];
+
+const NEW_PANE_PROMPT_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(
+        Action::NextChoice,
+        "next choice",
+        "Fill in the next offered value",
+    ),
+    offer(Action::Confirm, "confirm", "Use the value"),
+    offer(Action::Back, "cancel", "Cancel the new pane"),
+];
+
+const AGENT_FORM_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::NextField, "move", "Go to the next field"),
+    offer(Action::PrevField, "move", "Go to the previous field"),
+    offer(Action::Confirm, "save", "Save the agent"),
+    offer(Action::Back, "cancel", "Discard the changes"),
+];
+
+const DELETE_CONFIRM_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Confirm, "confirm", "Delete the agent"),
+    offer(Action::Yes, "confirm", "Delete the agent"),
+    offer(Action::Back, "cancel", "Keep the agent"),
+    offer(Action::No, "cancel", "Keep the agent"),
+];
+
+const AGENT_IMPORT_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(
+        Action::CycleConflict,
+        "conflict mode",
+        "Cycle how name conflicts are resolved",
+    ),
+    offer(Action::Confirm, "import", "Import the file"),
+    offer(Action::Back, "cancel", "Back to the agent list"),
+];
+
+const AGENT_EXPORT_ACTIONS: &[ModeAction] = &[
+    HELP,
+    offer(Action::Confirm, "export", "Export to the file"),
+    offer(Action::Back, "cancel", "Back to the agent list"),
+];
+
+const HELP_ACTIONS: &[ModeAction] = &[
+    offer(Action::Down, "scroll", "Scroll down"),
+    offer(Action::Up, "scroll", "Scroll up"),
+    offer(Action::Back, "close", "Close the help"),
+    offer(Action::Help, "close", "Close the help"),
+];
+
+/// Screens checked for key conflicts; the agent form is checked once, since
+/// creating and editing offer the same actions.
+const CHECKED_MODES: &[Mode] = &[
+    Mode::View,
+    Mode::AllSessions,
+    Mode::MovePaneTab,
+    Mode::AgentConfig,
+    Mode::NewPaneWorkspace,
+    Mode::NewPaneTabSelect,
+    Mode::NewPaneAgentSelect,
+    Mode::NewPanePrompt,
+    Mode::AgentFormCreate,
+    Mode::DeleteConfirm,
+    Mode::AgentImport,
+    Mode::AgentExport,
+    Mode::Help,
+];
+
+/// The actions offered in `mode`, in hint order. [`Keymap::from_map`]
+/// keeps configured keys from being shared by two of them.
+pub fn mode_actions(mode: Mode) -> &'static [ModeAction] {
+    match mode {
+        Mode::View => VIEW_ACTIONS,
+        Mode::MovePaneTab => MOVE_PANE_TAB_ACTIONS,
+        Mode::AllSessions => ALL_SESSIONS_ACTIONS,
+        Mode::AgentConfig => AGENT_CONFIG_ACTIONS,
+        Mode::NewPaneWorkspace => NEW_PANE_WORKSPACE_ACTIONS,
+        Mode::NewPaneTabSelect => NEW_PANE_TAB_SELECT_ACTIONS,
+        Mode::NewPaneAgentSelect => NEW_PANE_AGENT_SELECT_ACTIONS,
+        Mode::NewPanePrompt => NEW_PANE_PROMPT_ACTIONS,
+        Mode::AgentFormCreate | Mode::AgentFormEdit => AGENT_FORM_ACTIONS,
+        Mode::DeleteConfirm => DELETE_CONFIRM_ACTIONS,
+        Mode::AgentImport => AGENT_IMPORT_ACTIONS,
+        Mode::AgentExport => AGENT_EXPORT_ACTIONS,
+        Mode::Help => HELP_ACTIONS,
+    }
+}
+
+/// Keys bound to each action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<Action, Vec<KeyWithModifier>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|&action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|key| parse_key(key))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Build a keymap from the plugin configuration map. An action keeps its
+    /// default keys when its entry is missing, any key fails to parse, or one
+    /// of its keys is used by another action of the same screen; each such
+    /// conflict is returned alongside.
+    pub fn from_map(map: &BTreeMap<String, String>) -> (Self, Vec<MaestroError>) {
+        let mut keymap = Self::default();
+        let mut configured = BTreeSet::new();
+        for action in Action::ALL {
+            let Some(value) = map.get(&format!("key_{}", action.name())) else {
+                continue;
+            };
+            let keys: Option<Vec<KeyWithModifier>> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(parse_key)
+                .collect();
+            if let Some(keys) = keys {
+                keymap.bindings.insert(action, keys);
+                configured.insert(action);
+            }
+        }
+
+        let defaults = Self::default();
+        let mut conflicts = Vec::new();
+        while let Some((mode, key, first, second)) = keymap.find_conflict(&configured) {
+            // Reset the configured side; when both are, the one listed later.
+            let reset = if configured.contains(&second) {
+                second
+            } else {
+                first
+            };
+            keymap.bindings.insert(reset, defaults.keys(reset).to_vec());
+            configured.remove(&reset);
+            conflicts.push(MaestroError::KeyConflict {
+                key: format_key(&key),
+                first: first.name().to_string(),
+                second: second.name().to_string(),
+                screen: screen_name(mode).to_string(),
+                kept: reset.name().to_string(),
+            });
+        }
+        (keymap, conflicts)
+    }
+
+    /// A key shared by two actions of one screen, at least one of them
+    /// `configured`. Clashes between default keys are left alone.
+    fn find_conflict(
+        &self,
+        configured: &BTreeSet<Action>,
+    ) -> Option<(Mode, KeyWithModifier, Action, Action)> {
+        for &mode in CHECKED_MODES {
+            let actions = mode_actions(mode);
+            for (idx, first) in actions.iter().enumerate() {
+                for second in &actions[idx + 1..] {
+                    let (first, second) = (first.action, second.action);
+                    if first == second
+                        || !(configured.contains(&first) || configured.contains(&second))
+                    {
+                        continue;
+                    }
+                    let shared = self
+                        .keys_in(mode, first)
+                        .find(|key| self.keys_in(mode, second).any(|other| other == *key));
+                    if let Some(key) = shared {
+                        return Some((mode, key.clone(), first, second));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Every key bound to `action`.
+    pub fn keys(&self, action: Action) -> &[KeyWithModifier] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The keys of `action` that work in `mode`. Screens with a text input
+    /// leave plain characters to the input, so such bindings are skipped.
+    pub fn keys_in(&self, mode: Mode, action: Action) -> impl Iterator<Item = &KeyWithModifier> {
+        self.keys(action)
+            .iter()
+            .filter(move |key| !(mode.takes_text() && is_typed_char(key)))
+    }
+
+    /// The action `key` triggers in `mode`, if any.
+    pub fn action(&self, mode: Mode, key: &KeyWithModifier) -> Option<Action> {
+        mode_actions(mode)
+            .iter()
+            .map(|offered| offered.action)
+            .find(|&action| self.keys_in(mode, action).any(|bound| bound == key))
+    }
+
+    /// Status line hints for the actions of `mode` that `shown` keeps, e.g.
+    /// `j/k move • Enter focus`. Each action shows its first working key.
+    pub fn hints(&self, mode: Mode, shown: impl Fn(Action) -> bool) -> String {
+        let mut hints: Vec<(Vec<String>, &str)> = Vec::new();
+        for offered in mode_actions(mode).iter().filter(|o| shown(o.action)) {
+            let Some(key) = self.keys_in(mode, offered.action).next() else {
+                continue;
+            };
+            match hints.last_mut() {
+                Some((keys, label)) if *label == offered.label => keys.push(format_key(key)),
+                _ => hints.push((vec![format_key(key)], offered.label)),
+            }
+        }
+        hints
+            .iter()
+            .map(|(keys, label)| format!("{} {label}", keys.join("/")))
+            .collect::<Vec<_>>()
+            .join(" • ")
+    }
+
+    /// Help screen rows for `mode`: every working key of each action and
+    /// what it does. Consecutive actions described alike share a row.
+    pub fn help_entries(&self, mode: Mode) -> Vec<(String, &'static str)> {
+        let mut entries: Vec<(Vec<String>, &'static str)> = Vec::new();
+        for offered in mode_actions(mode) {
+            let keys = self.keys_in(mode, offered.action).map(format_key);
+            match entries.last_mut() {
+                Some((bound, help)) if *help == offered.help => bound.extend(keys),
+                _ => entries.push((keys.collect(), offered.help)),
+            }
+        }
+        entries
+            .into_iter()
+            .map(|(keys, help)| {
+                if keys.is_empty() {
+                    ("(unbound)".to_string(), help)
+                } else {
+                    (keys.join(", "), help)
+                }
+            })
+            .collect()
+    }
+}
+
+fn parse_key(key: &str) -> Option<KeyWithModifier> {
+    KeyWithModifier::from_str(key).ok()
+}
+
+/// A character typed without Ctrl or Alt, i.e. text for an input.
+fn is_typed_char(key: &KeyWithModifier) -> bool {
+    matches!(key.bare_key, BareKey::Char(_))
+        && !key.key_modifiers.contains(&KeyModifier::Ctrl)
+        && !key.key_modifiers.contains(&KeyModifier::Alt)
+}
+
+/// A key as the hints spell it: `j`, `J`, `Ctrl-n`, `Shift-Tab`, `↑`.
+pub fn format_key(key: &KeyWithModifier) -> String {
+    let mut modifiers: Vec<&str> = Vec::new();
+    for modifier in &key.key_modifiers {
+        match modifier {
+            KeyModifier::Ctrl => modifiers.push("Ctrl"),
+            KeyModifier::Alt => modifiers.push("Alt"),
+            KeyModifier::Super => modifiers.push("Super"),
+            // A shifted letter reads better as the capital.
+            KeyModifier::Shift if matches!(key.bare_key, BareKey::Char(c) if c.is_alphabetic()) => {
+            }
+            KeyModifier::Shift => modifiers.push("Shift"),
+        }
+    }
+    let shifted = key.key_modifiers.contains(&KeyModifier::Shift);
+    let bare = match key.bare_key {
+        BareKey::Char(' ') => "Space".to_string(),
+        BareKey::Char(c) if shifted => c.to_uppercase().to_string(),
+        BareKey::Char(c) => c.to_string(),
+        BareKey::Up => "↑".to_string(),
+        BareKey::Down => "↓".to_string(),
+        BareKey::Left => "←".to_string(),
+        BareKey::Right => "→".to_string(),
+        BareKey::Enter => "Enter".to_string(),
+        BareKey::Esc => "Esc".to_string(),
+        BareKey::Tab => "Tab".to_string(),
+        BareKey::Backspace => "Backspace".to_string(),
+        BareKey::Delete => "Del".to_string(),
+        BareKey::Home => "Home".to_string(),
+        BareKey::End => "End".to_string(),
+        BareKey::PageUp => "PgUp".to_string(),
+        BareKey::PageDown => "PgDn".to_string(),
+        BareKey::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    };
+    modifiers.push(&bare);
+    modifiers.join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(spec: &str) -> KeyWithModifier {
+        parse_key(spec).unwrap()
+    }
+
+    fn map(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_keys_parse() {
+        let keymap = Keymap::default();
+        for action in Action::ALL {
+            assert_eq!(
+                keymap.keys(action).len(),
+                action.default_keys().len(),
+                "{}",
+                action.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_action_per_mode() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(Mode::View, &key("d")), Some(Action::KillPane));
+        assert_eq!(
+            keymap.action(Mode::AgentConfig, &key("d")),
+            Some(Action::DeleteAgent)
+        );
+        assert_eq!(
+            keymap.action(Mode::AgentConfig, &key("J")),
+            Some(Action::MoveAgentDown)
+        );
+        assert_eq!(
+            keymap.action(Mode::AgentConfig, &key("Shift j")),
+            Some(Action::MoveAgentDown)
+        );
+        assert_eq!(
+            keymap.action(Mode::DeleteConfirm, &key("n")),
+            Some(Action::No)
+        );
+        assert_eq!(keymap.action(Mode::View, &key("Ctrl d")), None);
+        assert_eq!(keymap.action(Mode::View, &key("z")), None);
+    }
+
+    #[test]
+    fn test_text_modes_ignore_typed_chars() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(Mode::NewPaneWorkspace, &key("j")), None);
+        assert_eq!(
+            keymap.action(Mode::NewPaneWorkspace, &key("Ctrl n")),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            keymap.action(Mode::NewPaneWorkspace, &key("Down")),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            keymap.action(Mode::AgentFormEdit, &key("Shift Tab")),
+            Some(Action::PrevField)
+        );
+    }
+
+    #[test]
+    fn test_keymap_from_map() {
+        let (keymap, conflicts) = Keymap::from_map(&map(&[
+            ("key_down", "Down, Ctrl j"),
+            ("key_kill_pane", "Ctrl x"),
+            ("key_try_agent", ""),
+            ("key_new_pane", "Hyper n"),
+        ]));
+        assert_eq!(keymap.keys(Action::Down), [key("Down"), key("Ctrl j")]);
+        assert_eq!(keymap.action(Mode::View, &key("j")), None);
+        assert_eq!(
+            keymap.action(Mode::View, &key("Ctrl x")),
+            Some(Action::KillPane)
+        );
+        assert!(keymap.keys(Action::TryAgent).is_empty());
+        // Unparseable entries keep the defaults.
+        assert_eq!(keymap.keys(Action::NewPane), [key("n")]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_default_keys_do_not_conflict() {
+        let all: BTreeSet<Action> = Action::ALL.iter().copied().collect();
+        assert_eq!(Keymap::default().find_conflict(&all), None);
+    }
+
+    #[test]
+    fn test_keymap_from_map_conflicts() {
+        // `d` is the default key of kill_pane on the main screen.
+        let (keymap, conflicts) =
+            Keymap::from_map(&map(&[("key_new_pane", "d"), ("key_down", "Ctrl j")]));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Key d is bound to both kill_pane and new_pane on the Agent panes screen; \
+             new_pane keeps its default keys"
+        );
+        assert_eq!(keymap.keys(Action::NewPane), [key("n")]);
+        assert_eq!(keymap.action(Mode::View, &key("d")), Some(Action::KillPane));
+        // Other configured keys still apply.
+        assert_eq!(keymap.keys(Action::Down), [key("Ctrl j")]);
+
+        // When both sides are configured, the later one is reset.
+        let (keymap, conflicts) = Keymap::from_map(&map(&[
+            ("key_kill_pane", "Ctrl k"),
+            ("key_new_pane", "Ctrl k"),
+        ]));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(keymap.keys(Action::NewPane), [key("n")]);
+        assert_eq!(
+            keymap.action(Mode::View, &key("Ctrl k")),
+            Some(Action::KillPane)
+        );
+    }
+
+    #[test]
+    fn test_hints() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.hints(Mode::MovePaneTab, |_| true),
+            "? help • j/k move • Enter move here • Esc cancel"
+        );
+        assert_eq!(
+            keymap.hints(Mode::DeleteConfirm, |_| true),
+            "? help • Enter/y confirm • Esc/n cancel"
+        );
+        assert_eq!(
+            keymap.hints(Mode::NewPaneWorkspace, |a| a != Action::ForgetWorkspace),
+            "F1 help • ↓/↑ select • Tab accept • Ctrl-h hidden • Ctrl-r rescan projects • Enter continue • Esc cancel"
+        );
+
+        let (keymap, _) = Keymap::from_map(&map(&[("key_down", "Down"), ("key_up", "Up")]));
+        assert_eq!(
+            keymap.hints(Mode::MovePaneTab, |a| a != Action::Help),
+            "↓/↑ move • Enter move here • Esc cancel"
+        );
+    }
+
+    #[test]
+    fn test_help_entries() {
+        let (keymap, _) = Keymap::from_map(&map(&[("key_no", "")]));
+        assert_eq!(
+            keymap.help_entries(Mode::DeleteConfirm),
+            vec![
+                ("?, F1".to_string(), "Show the keys of this screen"),
+                ("Enter, y".to_string(), "Delete the agent"),
+                ("Esc".to_string(), "Keep the agent"),
+            ]
+        );
+        assert_eq!(
+            keymap.help_entries(Mode::NewPanePrompt)[0],
+            ("F1".to_string(), "Show the keys of this screen")
+        );
+
+        let (keymap, _) = Keymap::from_map(&map(&[("key_try_agent", "")]));
+        let entries = keymap.help_entries(Mode::AgentConfig);
+        assert_eq!(entries.len(), mode_actions(Mode::AgentConfig).len());
+        assert!(entries.contains(&(
+            "(unbound)".to_string(),
+            "Run the agent in a temporary floating pane"
+        )));
+    }
+
+    #[test]
+    fn test_format_key() {
+        assert_eq!(format_key(&key("Ctrl n")), "Ctrl-n");
+        assert_eq!(format_key(&key("Shift Tab")), "Shift-Tab");
+        assert_eq!(format_key(&key("Shift j")), "J");
+        assert_eq!(format_key(&key("Space")), "Space");
+        assert_eq!(format_key(&key("Alt Up")), "Alt-↑");
+    }
+}
@@ -0,0 +1,244 @@
+//! Per-agent tab layouts.
+//!
+//! An agent may carry a KDL layout body, written like the inside of a Zellij
+//! `tab` node. Every `agent` node in it marks where the agent runs and is
+//! replaced by a named command pane; string values may use the same
+//! placeholders as agent arguments (`{workspace}`, `{branch}`, ...).
+//!
+//! ```kdl
+//! layout {
+//!     pane split_direction="vertical" {
+//!         agent
+//!         pane command="cargo" { args "watch" "-x" "test"; }
+//!     }
+//! }
+//! ```
+
+use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
+
+use crate::error::{MaestroError, MaestroResult};
+use crate::template::{expand, TemplateContext};
+
+/// Node marking an agent pane in a layout.
+pub const AGENT_NODE: &str = "agent";
+
+/// A layout ready for `new_tabs_with_layout`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedLayout {
+    pub kdl: String,
+    /// Titles given to the agent panes, in layout order.
+    pub agent_titles: Vec<String>,
+}
+
+/// Parse a layout body and check that it places the agent somewhere.
+pub fn parse_layout(layout: &str) -> MaestroResult<KdlDocument> {
+    let doc: KdlDocument = layout
+        .parse()
+        .map_err(|e: kdl::KdlError| MaestroError::InvalidLayout(e.to_string()))?;
+    if count_agent_nodes(&doc) == 0 {
+        return Err(MaestroError::InvalidLayout(format!(
+            "no `{AGENT_NODE}` pane"
+        )));
+    }
+    Ok(doc)
+}
+
+fn count_agent_nodes(doc: &KdlDocument) -> usize {
+    doc.nodes()
+        .iter()
+        .map(|node| {
+            let own = usize::from(node.name().value() == AGENT_NODE);
+            own + node.children().map(count_agent_nodes).unwrap_or(0)
+        })
+        .sum()
+}
+
+/// Wrap a layout body in a tab named `tab_name`, expanding placeholders and
+/// replacing each `agent` node with a pane running `cmd`. `next_title` names
+/// each agent pane.
+pub fn render_layout(
+    layout: &str,
+    tab_name: &str,
+    cwd: Option<&str>,
+    ctx: &TemplateContext,
+    cmd: &[String],
+    mut next_title: impl FnMut() -> String,
+) -> MaestroResult<RenderedLayout> {
+    let mut body = parse_layout(layout)?;
+    let mut agent_titles = Vec::new();
+    rewrite_nodes(&mut body, ctx, cmd, &mut || {
+        let title = next_title();
+        agent_titles.push(title.clone());
+        title
+    });
+    body.clear_fmt_recursive();
+
+    let mut tab = KdlNode::new("tab");
+    tab.insert("name", tab_name.to_string());
+    if let Some(cwd) = cwd {
+        tab.insert("cwd", cwd.to_string());
+    }
+    tab.set_children(body);
+    let mut layout_node = KdlNode::new("layout");
+    let mut layout_children = KdlDocument::new();
+    layout_children.nodes_mut().push(tab);
+    layout_node.set_children(layout_children);
+
+    let mut doc = KdlDocument::new();
+    doc.nodes_mut().push(layout_node);
+    doc.fmt();
+    Ok(RenderedLayout {
+        kdl: doc.to_string(),
+        agent_titles,
+    })
+}
+
+fn rewrite_nodes(
+    doc: &mut KdlDocument,
+    ctx: &TemplateContext,
+    cmd: &[String],
+    next_title: &mut dyn FnMut() -> String,
+) {
+    for node in doc.nodes_mut() {
+        if node.name().value() == AGENT_NODE {
+            *node = agent_pane_node(node, cmd, next_title());
+            continue;
+        }
+        for entry in node.entries_mut() {
+            if let Some(value) = entry.value().as_string() {
+                let expanded = expand(value, ctx);
+                entry.set_value(KdlValue::String(expanded));
+            }
+        }
+        if let Some(children) = node.children_mut() {
+            rewrite_nodes(children, ctx, cmd, next_title);
+        }
+    }
+}
+
+/// A `pane` running the agent command, keeping any properties (`size`,
+/// `focus`, ...) set on the `agent` node.
+fn agent_pane_node(marker: &KdlNode, cmd: &[String], title: String) -> KdlNode {
+    let mut pane = KdlNode::new("pane");
+    for entry in marker.entries() {
+        if entry.name().is_some() {
+            pane.entries_mut().push(entry.clone());
+        }
+    }
+    pane.insert("name", title);
+    pane.insert("command", cmd.first().cloned().unwrap_or_default());
+    if cmd.len() > 1 {
+        let mut args = KdlNode::new("args");
+        for arg in &cmd[1..] {
+            args.push(KdlEntry::new(arg.clone()));
+        }
+        let mut children = KdlDocument::new();
+        children.nodes_mut().push(args);
+        pane.set_children(children);
+    }
+    pane
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = r#"
+pane split_direction="vertical" {
+    agent size="60%"
+    pane command="cargo" cwd="{workspace}" {
+        args "watch" "-x" "test"
+    }
+}
+pane
+"#;
+
+    fn ctx() -> TemplateContext {
+        TemplateContext {
+            workspace: "/src/app".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_layout_requires_agent() {
+        assert!(parse_layout(LAYOUT).is_ok());
+        assert!(matches!(
+            parse_layout("pane\npane"),
+            Err(MaestroError::InvalidLayout(_))
+        ));
+        assert!(matches!(
+            parse_layout("pane {"),
+            Err(MaestroError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_layout() {
+        let cmd = vec![
+            "claude".to_string(),
+            "--model".to_string(),
+            "opus".to_string(),
+        ];
+        let rendered = render_layout(LAYOUT, "app", Some("/src/app"), &ctx(), &cmd, || {
+            "app:1234".to_string()
+        })
+        .unwrap();
+        assert_eq!(rendered.agent_titles, vec!["app:1234".to_string()]);
+
+        let doc: KdlDocument = rendered.kdl.parse().unwrap();
+        let tab = doc
+            .get("layout")
+            .unwrap()
+            .children()
+            .unwrap()
+            .get("tab")
+            .unwrap();
+        assert_eq!(tab.get("name").unwrap().value().as_string(), Some("app"));
+        assert_eq!(
+            tab.get("cwd").unwrap().value().as_string(),
+            Some("/src/app")
+        );
+
+        let split = tab.children().unwrap().get("pane").unwrap();
+        let panes = split.children().unwrap().nodes();
+        let agent = &panes[0];
+        assert_eq!(agent.name().value(), "pane");
+        assert_eq!(
+            agent.get("name").unwrap().value().as_string(),
+            Some("app:1234")
+        );
+        assert_eq!(
+            agent.get("command").unwrap().value().as_string(),
+            Some("claude")
+        );
+        assert_eq!(agent.get("size").unwrap().value().as_string(), Some("60%"));
+        assert_eq!(
+            agent.children().unwrap().get_args("args"),
+            vec![&KdlValue::from("--model"), &KdlValue::from("opus")]
+        );
+        assert_eq!(
+            panes[1].get("cwd").unwrap().value().as_string(),
+            Some("/src/app")
+        );
+    }
+
+    #[test]
+    fn test_render_layout_titles_each_agent() {
+        let mut n = 0;
+        let rendered = render_layout(
+            "agent\nagent",
+            "tab",
+            None,
+            &ctx(),
+            &["codex".to_string()],
+            || {
+                n += 1;
+                format!("codex:{n}")
+            },
+        )
+        .unwrap();
+        assert_eq!(rendered.agent_titles, vec!["codex:1", "codex:2"]);
+        assert!(!rendered.kdl.contains("args"));
+    }
+}
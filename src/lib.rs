@@ -4,9 +4,19 @@
 //! terminal panes running AI coding agents like Claude, Cursor, Gemini, etc.
 
 pub mod agent;
+pub mod config;
 pub mod error;
 pub mod handlers;
+pub mod history;
+pub mod input;
+pub mod keymap;
+pub mod layout;
 pub mod model;
+pub mod placement;
+pub mod projects;
+pub mod registry;
+pub mod share;
+pub mod template;
 pub mod ui;
 pub mod utils;
 
@@ -14,6 +24,7 @@ pub mod utils;
 pub const WASI_HOST_MOUNT: &str = "/host";
 
 pub use agent::{Agent, AgentPane, PaneStatus};
+pub use config::Config;
 pub use error::{MaestroError, MaestroResult};
 pub use model::Model;
 pub use ui::{AgentFormField, Mode};
@@ -29,6 +40,7 @@ pub mod test_helpers {
             command: "echo".to_string(),
             args: vec![name.to_string()],
             note: None,
+            ..Default::default()
         }
     }
 }
@@ -3,12 +3,16 @@ use std::collections::BTreeMap;
 use zellij_tile::prelude::*;
 
 use maestro::agent::load_agents_default;
+use maestro::config::Config;
 use maestro::handlers::{
     apply_pane_update, apply_tab_update, handle_command_pane_exited, handle_command_pane_opened,
-    handle_command_pane_rerun, handle_key_event, handle_pane_closed, handle_permission_result,
-    handle_session_update,
+    handle_command_pane_rerun, handle_key_event, handle_pane_closed, handle_paste,
+    handle_permission_result, handle_run_command_result, handle_session_update,
+    request_availability_checks, request_dir_listings, request_git_branches,
 };
+use maestro::history::{default_history_path, load_history, save_history};
 use maestro::model::Model;
+use maestro::registry::{default_registry_path, load_registry, save_registry};
 use maestro::ui::{render_permissions_denied, render_permissions_requesting, render_ui};
 
 const REQUESTED_PERMISSIONS: &[PermissionType] = &[
@@ -26,8 +30,11 @@ pub struct Maestro {
 }
 
 impl ZellijPlugin for Maestro {
-    fn load(&mut self, _configuration: BTreeMap<String, String>) {
-        match load_agents_default() {
+    fn load(&mut self, configuration: BTreeMap<String, String>) {
+        self.model.config = Config::from_map(&configuration);
+        self.model.error_message = self.model.config.errors.join("; ");
+        self.model.host_cwd = Some(get_plugin_ids().initial_cwd);
+        match load_agents_default(&self.model.config) {
             Ok(list) => self.model.agents = list,
             Err(err) => {
                 eprintln!("maestro: load agents: {err}");
@@ -35,6 +42,15 @@ impl ZellijPlugin for Maestro {
             }
         }
 
+        match load_registry(&default_registry_path()) {
+            Ok(registry) => self.model.registry = registry,
+            Err(err) => eprintln!("maestro: load pane registry: {err}"),
+        }
+        match load_history(&default_history_path()) {
+            Ok(history) => self.model.workspace_history = history,
+            Err(err) => eprintln!("maestro: load workspace history: {err}"),
+        }
+
         request_permission(REQUESTED_PERMISSIONS);
 
         subscribe(&[
@@ -47,14 +63,17 @@ impl ZellijPlugin for Maestro {
             EventType::PaneClosed,
             EventType::BeforeClose,
             EventType::PermissionRequestResult,
+            EventType::RunCommandResult,
             EventType::Key,
+            EventType::PastedText,
         ]);
     }
 
     fn update(&mut self, event: Event) -> bool {
-        match event {
+        let should_render = match event {
             Event::PermissionRequestResult(status) => {
                 handle_permission_result(&mut self.model, status);
+                request_availability_checks(&mut self.model, true);
                 true
             }
             Event::TabUpdate(tabs) => {
@@ -65,8 +84,8 @@ impl ZellijPlugin for Maestro {
                 apply_pane_update(&mut self.model, manifest);
                 true
             }
-            Event::SessionUpdate(session_info, _resurrectable) => {
-                handle_session_update(&mut self.model, session_info);
+            Event::SessionUpdate(session_info, resurrectable) => {
+                handle_session_update(&mut self.model, session_info, resurrectable);
                 true
             }
             Event::CommandPaneOpened(pane_id, ctx) => {
@@ -85,13 +104,38 @@ impl ZellijPlugin for Maestro {
                 handle_pane_closed(&mut self.model, pane_id);
                 true
             }
+            Event::RunCommandResult(exit_code, stdout, _stderr, ctx) => {
+                handle_run_command_result(&mut self.model, exit_code, &stdout, ctx);
+                true
+            }
             Event::Key(key) => {
                 handle_key_event(&mut self.model, key);
                 true
             }
+            Event::PastedText(text) => {
+                handle_paste(&mut self.model, &text);
+                true
+            }
             Event::BeforeClose => true,
             _ => true,
+        };
+        // Start the directory listings the workspace suggestions are waiting on
+        // and the branch lookups of chosen workspaces.
+        request_dir_listings(&mut self.model);
+        request_git_branches(&mut self.model);
+        // Persist pane identities and workspace history whenever a handler changed them.
+        if self.model.registry.take_dirty() {
+            if let Err(err) = save_registry(&default_registry_path(), &self.model.registry) {
+                eprintln!("maestro: save pane registry: {err}");
+            }
+        }
+        if self.model.workspace_history.take_dirty() {
+            let path = default_history_path();
+            if let Err(err) = save_history(&path, &self.model.workspace_history) {
+                eprintln!("maestro: save workspace history: {err}");
+            }
         }
+        should_render
     }
 
     fn render(&mut self, rows: usize, cols: usize) {
@@ -104,6 +148,7 @@ impl ZellijPlugin for Maestro {
             return;
         }
 
+        self.model.rows = rows;
         print!("{}", render_ui(&self.model, cols));
     }
 }
@@ -1,17 +1,37 @@
 //! Plugin state model.
 
-use crate::agent::{Agent, AgentPane};
-use crate::ui::{AgentFormField, Mode};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::agent::{names_match, validate_agent_name, Agent, AgentPane, Availability, PaneStatus};
+use crate::config::Config;
+use crate::error::MaestroError;
+use crate::history::{now_secs, WorkspaceHistory};
+use crate::input::TextInput;
+use crate::placement::PlacementKind;
+use crate::projects::{match_projects, Project};
+use crate::registry::PaneRegistry;
+use crate::share::ConflictStrategy;
+use crate::template::{
+    expand_command, validate_templates, BranchCache, PromptVar, TemplateContext,
+};
+use crate::ui::{AgentFormField, Mode, MAX_SUGGESTIONS_DISPLAYED};
+use crate::utils::{
+    build_command, check_visible, expand_workspace_input, DirCache, PathContext,
+    WorkspaceSuggestion,
+};
 
 /// State for the agent create/edit form.
 #[derive(Debug, Default, Clone)]
 pub struct AgentForm {
-    pub name: String,
-    pub command: String,
-    pub args: String,
-    pub note: String,
+    pub name: TextInput,
+    pub command: TextInput,
+    pub args: TextInput,
+    pub note: TextInput,
     pub field: AgentFormField,
     pub target: Option<usize>,
+    /// Agent being cloned; the fields the form does not show are copied from it.
+    pub clone_of: Option<String>,
 }
 
 impl AgentForm {
@@ -19,7 +39,7 @@ impl AgentForm {
         *self = Self::default();
     }
 
-    pub fn current_input_mut(&mut self) -> &mut String {
+    pub fn current_input_mut(&mut self) -> &mut TextInput {
         match self.field {
             AgentFormField::Name => &mut self.name,
             AgentFormField::Command => &mut self.command,
@@ -29,14 +49,144 @@ impl AgentForm {
     }
 }
 
+/// Problems with the agent form's inputs, per field, shown as you type.
+#[derive(Debug, Default)]
+pub struct AgentFormIssues {
+    pub name: Option<MaestroError>,
+    pub command: Option<MaestroError>,
+    pub args: Option<MaestroError>,
+}
+
+impl AgentFormIssues {
+    pub fn get(&self, field: AgentFormField) -> Option<&MaestroError> {
+        match field {
+            AgentFormField::Name => self.name.as_ref(),
+            AgentFormField::Command => self.command.as_ref(),
+            AgentFormField::Args => self.args.as_ref(),
+            AgentFormField::Note => None,
+        }
+    }
+}
+
+/// The command an agent form would save, as shown under the form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandPreview {
+    /// Command and arguments quoted as a shell would need them.
+    pub argv: String,
+    /// The same with placeholders expanded from a sample context.
+    pub expanded: String,
+}
+
+impl AgentForm {
+    /// Check each field the way saving would, including name clashes with
+    /// `agents` other than the one being edited.
+    pub fn issues(&self, agents: &[Agent]) -> AgentFormIssues {
+        let name = self.name.trim();
+        let name_issue = if name.is_empty() {
+            Some(MaestroError::AgentNameRequired)
+        } else if let Err(err) = validate_agent_name(name) {
+            Some(err)
+        } else {
+            agents
+                .iter()
+                .enumerate()
+                .any(|(i, a)| Some(i) != self.target && names_match(&a.name, name))
+                .then(|| MaestroError::DuplicateAgentName(name.to_string()))
+        };
+        let command = self.command.trim().to_string();
+        let command_issue = if command.is_empty() {
+            Some(MaestroError::CommandRequired)
+        } else {
+            validate_templates([&command]).err()
+        };
+        let args_issue = match shell_words::split(&self.args) {
+            Ok(args) => validate_templates(&args).err(),
+            Err(e) => Some(MaestroError::InvalidAgentArgs(e.to_string())),
+        };
+        AgentFormIssues {
+            name: name_issue,
+            command: command_issue,
+            args: args_issue,
+        }
+    }
+
+    /// The command line the agent would run, built the way spawning builds
+    /// it and expanded with `ctx`; `None` without a command or while the
+    /// arguments do not parse.
+    pub fn preview(&self, ctx: &TemplateContext) -> Option<CommandPreview> {
+        let command = self.command.trim();
+        if command.is_empty() {
+            return None;
+        }
+        let agent = Agent {
+            command: command.to_string(),
+            args: shell_words::split(&self.args).ok()?,
+            ..Default::default()
+        };
+        let argv = build_command(&agent);
+        Some(CommandPreview {
+            argv: shell_words::join(&argv),
+            expanded: shell_words::join(expand_command(&argv, ctx)),
+        })
+    }
+}
+
+/// State for the agent import/export path prompt.
+#[derive(Debug, Default, Clone)]
+pub struct AgentTransfer {
+    pub path: TextInput,
+    pub strategy: ConflictStrategy,
+    /// Existing export target the user was warned about; submitting the
+    /// same path again overwrites it.
+    pub overwrite: Option<PathBuf>,
+}
+
+impl AgentTransfer {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// State for the help screen.
+#[derive(Debug, Default, Clone)]
+pub struct HelpView {
+    /// Screen whose keys are listed, and returned to on close.
+    pub mode: Mode,
+    /// Index of the first visible entry.
+    pub scroll: usize,
+}
+
 /// State for the new pane wizard flow.
 #[derive(Debug, Default, Clone)]
 pub struct PaneWizard {
-    pub workspace: String,
+    pub workspace: TextInput,
     pub browse_idx: usize,
     pub agent_filter: String,
     pub agent_idx: usize,
     pub tab_name: Option<String>,
+    /// Agent chosen in the picker, kept while prompting for variables.
+    pub agent_name: Option<String>,
+    /// Prompted variables still to be answered, in order.
+    pub prompts: Vec<PromptVar>,
+    pub prompt_idx: usize,
+    pub prompt_input: TextInput,
+    pub prompt_values: BTreeMap<String, String>,
+    /// Placement chosen in the wizard; `None` uses the agent's or the global default.
+    pub placement: Option<PlacementKind>,
+    /// Highlighted entry of the tab step: the current tab, then
+    /// `Model::tab_names`, then a new tab named by `tab_input`.
+    pub tab_idx: usize,
+    pub tab_input: TextInput,
+    /// Open the pane in the active tab instead of `tab_name`.
+    pub use_current_tab: bool,
+    /// Missing workspace offered for creation; confirmed by submitting it again.
+    pub create_workspace: Option<String>,
+    /// Suggestions for `workspace`, recomputed when it changes.
+    pub suggestions: Vec<WorkspaceSuggestion>,
+    /// Include hidden directories in the suggestions.
+    pub show_hidden: bool,
+    /// The directory `workspace` completes in is still being listed.
+    pub loading: bool,
 }
 
 impl PaneWizard {
@@ -45,21 +195,69 @@ impl PaneWizard {
     }
 }
 
+/// Agent panes found in another Zellij session.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionPanes {
+    pub name: String,
+    /// Tab names in position order, used to focus a pane's tab when switching.
+    pub tab_names: Vec<String>,
+    pub panes: Vec<AgentPane>,
+}
+
 /// The complete state of the Maestro plugin.
 #[derive(Debug, Default)]
 pub struct Model {
     pub permissions_granted: bool,
     pub permissions_denied: bool,
+    pub config: Config,
     pub agents: Vec<Agent>,
+    /// Executable availability keyed by agent command.
+    pub command_availability: BTreeMap<String, Availability>,
     pub agent_panes: Vec<AgentPane>,
+    /// Identity of panes Maestro spawned, persisted across reloads.
+    pub registry: PaneRegistry,
+    /// Panes opened by the config screen's try action, with the agent they
+    /// run. They are never listed as agent panes.
+    pub trial_panes: BTreeMap<u32, String>,
+    /// Workspaces agents were spawned in, persisted across reloads.
+    pub workspace_history: WorkspaceHistory,
+    /// Repositories under the configured project roots; `None` until scanned.
+    pub projects: Option<Vec<Project>>,
+    /// Directory listings behind the workspace suggestions.
+    pub dir_cache: DirCache,
+    /// Git branches of chosen workspaces, for the `{branch}` placeholder.
+    pub branches: BranchCache,
     pub tab_names: Vec<String>,
+    /// Name of the active tab of this session.
+    pub current_tab: Option<String>,
+    /// Last focused tiled terminal pane per tab name, the anchor for placements.
+    pub focused_panes: BTreeMap<String, u32>,
     pub session_name: Option<String>,
+    /// Agent panes of the other running sessions, sorted by session name.
+    pub other_sessions: Vec<SessionPanes>,
+    /// Exited sessions that can be resurrected and had agent panes, most recent first.
+    pub resurrectable_sessions: Vec<String>,
+    /// Host directory the plugin was launched with (what `/host` maps to).
+    pub host_cwd: Option<PathBuf>,
     pub mode: Mode,
     pub error_message: String,
+    /// One-shot informational message shown in the status line.
+    pub notice: String,
     pub selected_pane: usize,
+    /// Selection in the all-sessions list: live panes (see
+    /// [`Model::all_session_panes`]) followed by resurrectable sessions.
+    pub selected_session_pane: usize,
     pub selected_agent: usize,
+    /// Highlighted entry of the tab picker: a tab index, or `tab_names.len()` for a new tab.
+    pub move_tab_idx: usize,
+    /// Lowercased names of agents marked for export.
+    pub marked_agents: BTreeSet<String>,
     pub agent_form: AgentForm,
+    pub agent_transfer: AgentTransfer,
     pub pane_wizard: PaneWizard,
+    pub help: HelpView,
+    /// Height of the plugin pane at the last render.
+    pub rows: usize,
 }
 
 impl Model {
@@ -68,6 +266,151 @@ impl Model {
         self.error_message.clear();
     }
 
+    /// Look up whether an agent's command was found on the host.
+    pub fn agent_availability(&self, agent: &Agent) -> Availability {
+        self.command_availability
+            .get(agent.command.trim())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Recompute the wizard's workspace suggestions after its input changed:
+    /// recent workspaces while it is empty, otherwise matching projects and
+    /// directories. Directory listings come from [`Model::dir_cache`]; while
+    /// one is still loading only the projects are offered.
+    pub fn refresh_workspace_suggestions(&mut self) {
+        let now = now_secs();
+        let input = self.pane_wizard.workspace.clone();
+        if input.trim().is_empty() {
+            self.pane_wizard.suggestions = self
+                .workspace_history
+                .ranked(now)
+                .into_iter()
+                .map(|path| WorkspaceSuggestion {
+                    path,
+                    ..Default::default()
+                })
+                .collect();
+            self.pane_wizard.loading = false;
+            self.resolve_shown_suggestions();
+            return;
+        }
+        let mut suggestions: Vec<WorkspaceSuggestion> = Vec::new();
+        // A bare name searches the known repositories before the directory.
+        if !input.contains('/') && !input.starts_with('~') {
+            let projects = self.projects.as_deref().unwrap_or_default();
+            suggestions.extend(match_projects(projects, &input).into_iter().map(
+                |(project, indices)| {
+                    let path = project.path.to_string_lossy().into_owned();
+                    // Indices are into the name, which ends the path.
+                    let offset = path.chars().count() - project.name.chars().count();
+                    WorkspaceSuggestion {
+                        matched: indices.into_iter().map(|i| i + offset).collect(),
+                        path,
+                        git: Some(true),
+                    }
+                },
+            ));
+        }
+        let ctx = self.path_context();
+        let expanded = expand_workspace_input(&input, &ctx);
+        let mut loading = false;
+        if check_visible(&expanded, &ctx).is_ok() {
+            let show_hidden = self.pane_wizard.show_hidden;
+            match self.dir_cache.path_suggestions(&expanded, now, show_hidden) {
+                Some(listed) => {
+                    for suggestion in listed {
+                        if !suggestions.iter().any(|s| s.path == suggestion.path) {
+                            suggestions.push(suggestion);
+                        }
+                    }
+                }
+                None => loading = true,
+            }
+        }
+        self.pane_wizard.suggestions = suggestions;
+        self.pane_wizard.loading = loading;
+        self.resolve_shown_suggestions();
+    }
+
+    /// Check the suggestions that can be on screen for the selected one
+    /// (see [`crate::ui`]) for `.git`, leaving the rest of a large listing
+    /// unchecked.
+    pub fn resolve_shown_suggestions(&mut self) {
+        let shown = self.pane_wizard.browse_idx + MAX_SUGGESTIONS_DISPLAYED;
+        for suggestion in self.pane_wizard.suggestions.iter_mut().take(shown) {
+            suggestion.resolve_git();
+        }
+    }
+
+    /// The text input that has focus in the current mode, if any.
+    pub fn active_input_mut(&mut self) -> Option<&mut TextInput> {
+        match self.mode {
+            Mode::NewPaneWorkspace => Some(&mut self.pane_wizard.workspace),
+            Mode::NewPaneTabSelect => Some(&mut self.pane_wizard.tab_input),
+            Mode::NewPanePrompt => Some(&mut self.pane_wizard.prompt_input),
+            Mode::AgentFormCreate | Mode::AgentFormEdit => {
+                Some(self.agent_form.current_input_mut())
+            }
+            Mode::AgentImport | Mode::AgentExport => Some(&mut self.agent_transfer.path),
+            _ => None,
+        }
+    }
+
+    /// Running agent panes whose workspace is `path`.
+    pub fn running_agents_in(&self, path: &str) -> usize {
+        let path = path.trim_end_matches('/');
+        self.agent_panes
+            .iter()
+            .filter(|p| p.status == PaneStatus::Running)
+            .filter(|p| p.workspace_path.trim_end_matches('/') == path)
+            .count()
+    }
+
+    /// Workspace of the agent pane focused in the current tab.
+    pub fn focused_workspace(&self) -> Option<&str> {
+        self.current_tab
+            .as_ref()
+            .and_then(|tab| self.focused_panes.get(tab))
+            .and_then(|id| self.agent_panes.iter().find(|p| p.pane_id == Some(*id)))
+            .map(|pane| pane.workspace_path.as_str())
+    }
+
+    /// Directories workspace input is resolved against: the plugin `cwd`,
+    /// the configured home directory and, as the base for relative paths,
+    /// the workspace of the agent pane focused in the current tab. Zellij
+    /// does not report the cwd of other panes, so any other focused pane
+    /// leaves relative paths on the plugin `cwd`.
+    pub fn path_context(&self) -> PathContext {
+        let base = self
+            .focused_workspace()
+            .and_then(crate::utils::resolve_workspace_path)
+            .map(|path| match &self.host_cwd {
+                Some(cwd) => cwd.join(path),
+                None => path,
+            });
+        PathContext {
+            host_cwd: self.host_cwd.clone(),
+            home: self.config.home.clone(),
+            base,
+        }
+    }
+
+    /// Agent panes of every session with their session name, the current
+    /// session first, in display order.
+    pub fn all_session_panes(&self) -> Vec<(&str, &AgentPane)> {
+        let current = self.session_name.as_deref().unwrap_or_default();
+        self.agent_panes
+            .iter()
+            .map(|p| (current, p))
+            .chain(
+                self.other_sessions
+                    .iter()
+                    .flat_map(|s| s.panes.iter().map(|p| (s.name.as_str(), p))),
+            )
+            .collect()
+    }
+
     /// Ensure selection indices stay within valid bounds after list changes.
     pub fn clamp_selections(&mut self) {
         let pane_len = self.agent_panes.len();
@@ -77,6 +420,17 @@ impl Model {
             self.selected_pane = pane_len.saturating_sub(1);
         }
 
+        let session_pane_len = self.agent_panes.len()
+            + self
+                .other_sessions
+                .iter()
+                .map(|s| s.panes.len())
+                .sum::<usize>()
+            + self.resurrectable_sessions.len();
+        if self.selected_session_pane >= session_pane_len {
+            self.selected_session_pane = session_pane_len.saturating_sub(1);
+        }
+
         let agent_len = self.agents.len();
         if agent_len == 0 {
             self.selected_agent = 0;
@@ -89,7 +443,6 @@ impl Model {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::agent::{AgentPane, PaneStatus};
     use crate::test_helpers::create_test_agent;
 
     fn create_test_pane(agent_name: &str, tab_name: &str) -> AgentPane {
@@ -100,7 +453,10 @@ mod tests {
             pane_id: Some(1),
             workspace_path: String::new(),
             agent_name: agent_name.to_string(),
+            candidates: Vec::new(),
             status: PaneStatus::Running,
+            floating: false,
+            pinned: false,
         }
     }
 
@@ -141,4 +497,162 @@ mod tests {
         assert_eq!(model.selected_pane, 0);
         assert_eq!(model.selected_agent, 1);
     }
+
+    #[test]
+    fn test_agent_form_issues() {
+        let agents = vec![create_test_agent("claude"), create_test_agent("codex")];
+        let mut form = AgentForm::default();
+        let issues = form.issues(&agents);
+        assert!(matches!(issues.name, Some(MaestroError::AgentNameRequired)));
+        assert!(matches!(
+            issues.command,
+            Some(MaestroError::CommandRequired)
+        ));
+        assert!(issues.args.is_none());
+
+        form.name = "Claude".into();
+        form.command = "claude".into();
+        form.args = "--dir {workspce} \"open".into();
+        let issues = form.issues(&agents);
+        assert!(matches!(
+            issues.name,
+            Some(MaestroError::DuplicateAgentName(_))
+        ));
+        assert!(issues.command.is_none());
+        assert!(matches!(
+            issues.args,
+            Some(MaestroError::InvalidAgentArgs(_))
+        ));
+
+        // Editing an agent may keep its own name.
+        form.target = Some(0);
+        form.args = "--dir {workspce}".into();
+        let issues = form.issues(&agents);
+        assert!(issues.name.is_none());
+        assert!(matches!(
+            issues.args,
+            Some(MaestroError::UnknownPlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn test_agent_form_preview() {
+        let ctx = TemplateContext {
+            workspace_name: "app".to_string(),
+            ..Default::default()
+        };
+        let mut form = AgentForm {
+            command: "claude".into(),
+            args: "--append-system-prompt 'be brief' --name {workspace_name}".into(),
+            ..Default::default()
+        };
+        let preview = form.preview(&ctx).unwrap();
+        assert_eq!(
+            preview.argv,
+            "claude --append-system-prompt 'be brief' --name {workspace_name}"
+        );
+        assert_eq!(
+            preview.expanded,
+            "claude --append-system-prompt 'be brief' --name app"
+        );
+
+        form.args = "'unterminated".into();
+        assert_eq!(form.preview(&ctx), None);
+    }
+
+    #[test]
+    fn test_only_shown_suggestions_are_checked_for_git() {
+        let mut model = Model::default();
+        model.pane_wizard.suggestions = (0..20)
+            .map(|i| WorkspaceSuggestion {
+                path: format!("/host/dir{i}"),
+                ..Default::default()
+            })
+            .collect();
+        model.pane_wizard.browse_idx = 2;
+        model.resolve_shown_suggestions();
+        let checked = model
+            .pane_wizard
+            .suggestions
+            .iter()
+            .filter(|s| s.git.is_some())
+            .count();
+        assert_eq!(checked, 2 + MAX_SUGGESTIONS_DISPLAYED);
+    }
+
+    #[test]
+    fn test_workspace_suggestions_match_projects() {
+        let mut model = Model {
+            projects: Some(vec![Project {
+                name: "maestro".to_string(),
+                path: PathBuf::from("/host/src/maestro"),
+            }]),
+            ..Default::default()
+        };
+        model.pane_wizard.workspace = "mstr".into();
+        model.refresh_workspace_suggestions();
+        assert_eq!(
+            model.pane_wizard.suggestions,
+            vec![WorkspaceSuggestion {
+                path: "/host/src/maestro".to_string(),
+                // "/host/src/" is 10 chars, then m-s-t-r of "maestro".
+                matched: vec![10, 13, 14, 15],
+                git: Some(true),
+            }]
+        );
+
+        model.pane_wizard.workspace = "src/mstr".into();
+        model.refresh_workspace_suggestions();
+        assert!(!model
+            .pane_wizard
+            .suggestions
+            .iter()
+            .any(|s| s.path == "/host/src/maestro"));
+    }
+
+    #[test]
+    fn test_path_context_uses_focused_agent_workspace() {
+        let mut model = Model {
+            host_cwd: Some(PathBuf::from("/home/me")),
+            current_tab: Some("tab1".to_string()),
+            ..Default::default()
+        };
+        model.focused_panes.insert("tab1".to_string(), 1);
+        let mut pane = create_test_pane("agent1", "tab1");
+        pane.workspace_path = "src/app".to_string();
+        model.agent_panes.push(pane);
+
+        let ctx = model.path_context();
+        assert_eq!(ctx.base, Some(PathBuf::from("/home/me/src/app")));
+
+        model.focused_panes.insert("tab1".to_string(), 9);
+        assert_eq!(model.path_context().base, None);
+    }
+
+    #[test]
+    fn test_all_session_panes_current_first() {
+        let mut model = Model {
+            session_name: Some("here".to_string()),
+            ..Default::default()
+        };
+        model.agent_panes.push(create_test_pane("local", "tab1"));
+        model.other_sessions.push(SessionPanes {
+            name: "there".to_string(),
+            tab_names: vec!["tab1".to_string()],
+            panes: vec![create_test_pane("a", "tab1"), create_test_pane("b", "tab1")],
+        });
+        model.selected_session_pane = 7;
+        model.clamp_selections();
+
+        let entries: Vec<(&str, &str)> = model
+            .all_session_panes()
+            .into_iter()
+            .map(|(session, pane)| (session, pane.agent_name.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![("here", "local"), ("there", "a"), ("there", "b")]
+        );
+        assert_eq!(model.selected_session_pane, 2);
+    }
 }
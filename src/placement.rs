@@ -0,0 +1,198 @@
+//! Where a spawned agent pane is placed in its tab.
+
+use serde::{Deserialize, Serialize};
+use zellij_tile::prelude::FloatingPaneCoordinates;
+
+use crate::error::{MaestroError, MaestroResult};
+
+/// How a new agent pane is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlacementKind {
+    /// Zellij's default tiled placement.
+    #[default]
+    Tiled,
+    /// A floating pane, optionally with explicit geometry.
+    Floating,
+    /// Stacked with the focused pane.
+    Stacked,
+    /// Replacing the focused pane.
+    InPlace,
+}
+
+impl PlacementKind {
+    pub const ALL: [PlacementKind; 4] = [
+        PlacementKind::Tiled,
+        PlacementKind::Floating,
+        PlacementKind::Stacked,
+        PlacementKind::InPlace,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PlacementKind::Tiled => "tiled",
+            PlacementKind::Floating => "floating",
+            PlacementKind::Stacked => "stacked",
+            PlacementKind::InPlace => "in-place",
+        }
+    }
+
+    /// What the placement does, for the wizard.
+    pub fn help(self) -> &'static str {
+        match self {
+            PlacementKind::Tiled => "where Zellij puts new panes",
+            PlacementKind::Floating => "a floating pane",
+            PlacementKind::Stacked => "stacked with the focused pane",
+            PlacementKind::InPlace => "replaces the focused pane",
+        }
+    }
+
+    /// Parse a placement name as written in config (`floating`, `in-place`, ...).
+    pub fn parse(value: &str) -> MaestroResult<Self> {
+        let normalized = value.trim().to_lowercase().replace('_', "-");
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.label() == normalized)
+            .ok_or_else(|| MaestroError::InvalidPlacement(value.to_string()))
+    }
+}
+
+/// A placement with optional floating geometry. Sizes and positions use
+/// Zellij's notation: a cell count (`80`) or a percentage (`50%`).
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Placement {
+    #[serde(default)]
+    pub kind: PlacementKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<String>,
+}
+
+impl Placement {
+    pub fn new(kind: PlacementKind) -> Self {
+        Self {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    /// Floating geometry for Zellij, if any was given.
+    pub fn coordinates(&self) -> Option<FloatingPaneCoordinates> {
+        FloatingPaneCoordinates::new(
+            self.x.clone(),
+            self.y.clone(),
+            self.width.clone(),
+            self.height.clone(),
+            None,
+        )
+    }
+
+    /// Short description, including floating geometry when set.
+    pub fn describe(&self) -> String {
+        let geometry: Vec<String> = [
+            ("x", &self.x),
+            ("y", &self.y),
+            ("width", &self.width),
+            ("height", &self.height),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| format!("{key}={v}")))
+        .collect();
+        if self.kind != PlacementKind::Floating || geometry.is_empty() {
+            self.kind.label().to_string()
+        } else {
+            format!("{} {}", self.kind.label(), geometry.join(" "))
+        }
+    }
+
+    /// Check that the geometry values are sizes Zellij understands.
+    pub fn validate(&self) -> MaestroResult<()> {
+        for value in [&self.x, &self.y, &self.width, &self.height]
+            .into_iter()
+            .flatten()
+        {
+            let number = value.trim().strip_suffix('%').unwrap_or(value.trim());
+            if number.parse::<usize>().is_err() {
+                return Err(MaestroError::InvalidPlacement(value.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Step through placement overrides in the wizard: `None` (use the agent's
+/// or the global default) followed by every kind.
+pub fn next_placement_override(current: Option<PlacementKind>) -> Option<PlacementKind> {
+    match current {
+        None => Some(PlacementKind::ALL[0]),
+        Some(kind) => {
+            let idx = PlacementKind::ALL.iter().position(|k| *k == kind)?;
+            PlacementKind::ALL.get(idx + 1).copied()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_placement_kind() {
+        assert_eq!(
+            PlacementKind::parse("in_place").unwrap(),
+            PlacementKind::InPlace
+        );
+        assert_eq!(
+            PlacementKind::parse(" In-Place ").unwrap(),
+            PlacementKind::InPlace
+        );
+        // Plugins cannot split a chosen pane in a chosen direction.
+        for unsupported in ["sideways", "split-right", "split-down"] {
+            assert!(matches!(
+                PlacementKind::parse(unsupported),
+                Err(MaestroError::InvalidPlacement(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_placement_geometry() {
+        let placement = Placement {
+            kind: PlacementKind::Floating,
+            width: Some("80%".to_string()),
+            height: Some("30".to_string()),
+            ..Default::default()
+        };
+        assert!(placement.validate().is_ok());
+        assert!(placement.coordinates().is_some());
+        assert_eq!(placement.describe(), "floating width=80% height=30");
+        assert!(Placement::new(PlacementKind::Floating)
+            .coordinates()
+            .is_none());
+
+        let invalid = Placement {
+            x: Some("left".to_string()),
+            ..placement
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_next_placement_override_cycles() {
+        let mut current = None;
+        let mut seen = Vec::new();
+        loop {
+            current = next_placement_override(current);
+            match current {
+                Some(kind) => seen.push(kind),
+                None => break,
+            }
+        }
+        assert_eq!(seen, PlacementKind::ALL.to_vec());
+    }
+}
@@ -0,0 +1,145 @@
+//! Git repositories found under configured project roots.
+//!
+//! Roots are scanned once, down to a bounded depth, for directories holding
+//! a `.git` entry. The result is cached in the model and fuzzy-searched by
+//! repository name from the new-pane wizard.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories never descended into while scanning.
+const IGNORED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Default depth below each root at which repositories are looked for.
+pub const DEFAULT_PROJECT_DEPTH: usize = 3;
+
+/// A repository found under a project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// Directory name of the repository.
+    pub name: String,
+    /// Path as seen by the plugin (under `/host`).
+    pub path: PathBuf,
+}
+
+/// Find repositories under `roots`, at most `max_depth` levels below each
+/// root. Repositories are not searched for nested ones; hidden directories,
+/// `node_modules` and `target` are skipped. Sorted by path.
+pub fn scan_projects(roots: &[PathBuf], max_depth: usize) -> Vec<Project> {
+    let mut projects = Vec::new();
+    for root in roots {
+        scan_dir(root, max_depth, &mut projects);
+    }
+    projects.sort_by(|a, b| a.path.cmp(&b.path));
+    projects.dedup_by(|a, b| a.path == b.path);
+    projects
+}
+
+fn scan_dir(dir: &Path, depth_left: usize, projects: &mut Vec<Project>) {
+    if dir.join(".git").exists() {
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        projects.push(Project {
+            name,
+            path: dir.to_path_buf(),
+        });
+        return;
+    }
+    if depth_left == 0 {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || IGNORED_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            scan_dir(&entry.path(), depth_left - 1, projects);
+        }
+    }
+}
+
+/// Projects whose name fuzzy-matches `query`, best match first, with the
+/// char indices of the name that matched.
+pub fn match_projects<'a>(projects: &'a [Project], query: &str) -> Vec<(&'a Project, Vec<usize>)> {
+    use fuzzy_matcher::skim::SkimMatcherV2;
+    use fuzzy_matcher::FuzzyMatcher;
+
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(&Project, i64, Vec<usize>)> = projects
+        .iter()
+        .filter_map(|p| {
+            let (score, indices) = matcher.fuzzy_indices(&p.name, query)?;
+            Some((p, score, indices))
+        })
+        .collect();
+    scored.sort_by_key(|&(_, score, _)| std::cmp::Reverse(score));
+    scored
+        .into_iter()
+        .map(|(project, _, indices)| (project, indices))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_repo(root: &Path, rel: &str) {
+        fs::create_dir_all(root.join(rel).join(".git")).unwrap();
+    }
+
+    #[test]
+    fn test_scan_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        make_repo(root, "maestro");
+        make_repo(root, "work/api");
+        make_repo(root, "work/api/vendor/nested");
+        make_repo(root, "a/b/c/too-deep");
+        make_repo(root, "web/node_modules/dep");
+        make_repo(root, ".cache/hidden");
+        fs::create_dir_all(root.join("empty")).unwrap();
+
+        let projects = scan_projects(&[root.to_path_buf()], 3);
+        let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["maestro", "api"]);
+        assert_eq!(projects[1].path, root.join("work/api"));
+    }
+
+    #[test]
+    fn test_scan_projects_depth_and_missing_root() {
+        let dir = tempfile::tempdir().unwrap();
+        make_repo(dir.path(), "a/b/repo");
+
+        assert!(scan_projects(&[dir.path().to_path_buf()], 2).is_empty());
+        assert_eq!(scan_projects(&[dir.path().to_path_buf()], 3).len(), 1);
+        assert!(scan_projects(&[dir.path().join("missing")], 3).is_empty());
+    }
+
+    #[test]
+    fn test_match_projects() {
+        let projects: Vec<Project> = ["maestro", "zellij", "my-app"]
+            .iter()
+            .map(|name| Project {
+                name: name.to_string(),
+                path: PathBuf::from("/host/src").join(name),
+            })
+            .collect();
+
+        let matches = match_projects(&projects, "zlj");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0.name, "zellij");
+        assert_eq!(matches[0].1, vec![0, 3, 5]);
+        assert_eq!(match_projects(&projects, "ma")[0].0.name, "maestro");
+        assert!(match_projects(&projects, "  ").is_empty());
+    }
+}
@@ -0,0 +1,435 @@
+//! Persistent identity of spawned agent panes.
+//!
+//! Every pane Maestro spawns gets a UUID, kept in the context of its
+//! command pane events rather than on screen. The registry maps that UUID
+//! (and, once known, the Zellij pane id) back to the agent that was
+//! launched, so panes are recognised across plugin reloads without guessing
+//! from the command line.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use kdl::{KdlDocument, KdlNode};
+use uuid::Uuid;
+
+use crate::error::{MaestroError, MaestroResult};
+
+/// What Maestro knows about a pane it spawned.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PaneRecord {
+    /// UUID identifying the spawned pane.
+    pub id: String,
+    /// Session the pane was spawned in.
+    pub session: String,
+    /// Zellij pane id once the pane has opened.
+    pub pane_id: Option<u32>,
+    /// Display name of the agent (and variant) that was launched.
+    pub agent_name: String,
+    pub workspace_path: String,
+    pub tab_name: String,
+    /// Command line the pane was started with.
+    pub command: Vec<String>,
+}
+
+/// Records of spawned panes, persisted next to `agents.kdl`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PaneRegistry {
+    records: Vec<PaneRecord>,
+    dirty: bool,
+}
+
+impl PaneRegistry {
+    pub fn records(&self) -> &[PaneRecord] {
+        &self.records
+    }
+
+    /// Add a record, replacing any previous record with the same UUID.
+    pub fn insert(&mut self, record: PaneRecord) {
+        self.records.retain(|r| r.id != record.id);
+        self.records.push(record);
+        self.dirty = true;
+    }
+
+    /// Find the record whose UUID appears in `title`.
+    pub fn find_by_title(&self, title: &str) -> Option<&PaneRecord> {
+        let id = pane_uuid(title)?;
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    /// Find a record of `session` that was started with `command_line` and
+    /// is not bound to a live pane (`is_live`), for panes whose id is not
+    /// known yet (layout panes) or changed (a resurrected session).
+    pub fn find_by_command(
+        &self,
+        session: &str,
+        command_line: &str,
+        is_live: impl Fn(u32) -> bool,
+    ) -> Option<&PaneRecord> {
+        let running: Vec<&str> = command_line.split_whitespace().collect();
+        self.records.iter().find(|r| {
+            let recorded: Vec<&str> = r
+                .command
+                .iter()
+                .flat_map(|a| a.split_whitespace())
+                .collect();
+            sessions_match(&r.session, session)
+                && !recorded.is_empty()
+                // Resumed panes run the recorded command plus resume args.
+                && running.starts_with(&recorded)
+                && !r.pane_id.is_some_and(&is_live)
+        })
+    }
+
+    /// Find the record bound to `pane_id` in `session`.
+    pub fn find_by_pane_id(&self, session: &str, pane_id: u32) -> Option<&PaneRecord> {
+        self.records
+            .iter()
+            .find(|r| r.pane_id == Some(pane_id) && sessions_match(&r.session, session))
+    }
+
+    /// Bind the record with UUID `id` to its Zellij pane id in `session`.
+    pub fn bind_pane_id(&mut self, id: &str, session: &str, pane_id: u32) {
+        // Pane ids are reused once a pane closes; drop stale bindings.
+        for record in &mut self.records {
+            if record.id != id
+                && record.pane_id == Some(pane_id)
+                && sessions_match(&record.session, session)
+            {
+                record.pane_id = None;
+                self.dirty = true;
+            }
+        }
+        if let Some(record) = self.records.iter_mut().find(|r| r.id == id) {
+            let moved = !session.is_empty() && record.session != session;
+            if record.pane_id != Some(pane_id) || moved {
+                record.pane_id = Some(pane_id);
+                if moved {
+                    record.session = session.to_string();
+                }
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Record that the pane `pane_id` in `session` moved to `tab_name`.
+    pub fn set_tab(&mut self, session: &str, pane_id: u32, tab_name: &str) {
+        if let Some(record) = self
+            .records
+            .iter_mut()
+            .find(|r| r.pane_id == Some(pane_id) && sessions_match(&r.session, session))
+        {
+            if record.tab_name != tab_name {
+                record.tab_name = tab_name.to_string();
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Forget the pane `pane_id` in `session` after it closed.
+    pub fn remove_pane(&mut self, session: &str, pane_id: u32) {
+        let before = self.records.len();
+        self.records
+            .retain(|r| !(r.pane_id == Some(pane_id) && sessions_match(&r.session, session)));
+        if self.records.len() != before {
+            self.dirty = true;
+        }
+    }
+
+    /// Records of panes spawned in `session`.
+    pub fn session_records(&self, session: &str) -> Vec<&PaneRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.session == session)
+            .collect()
+    }
+
+    /// Forget every pane of `session`.
+    pub fn remove_session(&mut self, session: &str) {
+        self.retain_sessions(|name| name != session);
+    }
+
+    /// Keep only records whose session satisfies `keep`; records with an
+    /// unknown session are always kept.
+    pub fn retain_sessions(&mut self, keep: impl Fn(&str) -> bool) {
+        let before = self.records.len();
+        self.records
+            .retain(|r| r.session.is_empty() || keep(&r.session));
+        if self.records.len() != before {
+            self.dirty = true;
+        }
+    }
+
+    /// Whether the registry changed since it was last saved, resetting the flag.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+fn sessions_match(a: &str, b: &str) -> bool {
+    a.is_empty() || b.is_empty() || a == b
+}
+
+/// Extract the UUID from a pane title of the form `label:uuid`.
+pub fn pane_uuid(title: &str) -> Option<&str> {
+    let (_, id) = title.rsplit_once(':')?;
+    let id = id.trim();
+    Uuid::parse_str(id).ok().map(|_| id)
+}
+
+/// Get the default registry path (`~/.config/maestro/panes.kdl`).
+pub fn default_registry_path() -> PathBuf {
+    crate::agent::config_base_dir().join("panes.kdl")
+}
+
+/// Load the pane registry, treating a missing file as empty.
+pub fn load_registry(path: &Path) -> MaestroResult<PaneRegistry> {
+    let data = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(PaneRegistry::default()),
+        Err(e) => {
+            return Err(MaestroError::FileRead {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            })
+        }
+    };
+    parse_registry_kdl(&data)
+}
+
+/// Save the pane registry, creating the config directory if needed.
+pub fn save_registry(path: &Path, registry: &PaneRegistry) -> MaestroResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| MaestroError::DirectoryCreate {
+            path: parent.to_path_buf(),
+            message: e.to_string(),
+        })?;
+    }
+    fs::write(path, registry_to_kdl(registry).as_bytes()).map_err(|e| MaestroError::FileWrite {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+fn parse_registry_kdl(data: &str) -> MaestroResult<PaneRegistry> {
+    let doc: KdlDocument = data
+        .parse()
+        .map_err(|e: kdl::KdlError| MaestroError::ConfigParse(e.to_string()))?;
+    let mut registry = PaneRegistry::default();
+    for node in doc.nodes().iter().filter(|n| n.name().value() == "pane") {
+        let string = |key: &str| {
+            node.get(key)
+                .and_then(|e| e.value().as_string())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let id = string("id");
+        if Uuid::parse_str(&id).is_err() {
+            continue;
+        }
+        let command = node
+            .children()
+            .and_then(|c| c.get("cmd"))
+            .map(|cmd| {
+                cmd.entries()
+                    .iter()
+                    .filter_map(|e| e.value().as_string().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        registry.records.push(PaneRecord {
+            id,
+            session: string("session"),
+            pane_id: node
+                .get("pane_id")
+                .and_then(|e| e.value().as_i64())
+                .and_then(|id| u32::try_from(id).ok()),
+            agent_name: string("agent"),
+            workspace_path: string("workspace"),
+            tab_name: string("tab"),
+            command,
+        });
+    }
+    Ok(registry)
+}
+
+fn registry_to_kdl(registry: &PaneRegistry) -> String {
+    let mut doc = KdlDocument::new();
+    for record in &registry.records {
+        let mut node = KdlNode::new("pane");
+        node.insert("id", record.id.clone());
+        node.insert("session", record.session.clone());
+        if let Some(pane_id) = record.pane_id {
+            node.insert("pane_id", i64::from(pane_id));
+        }
+        node.insert("agent", record.agent_name.clone());
+        node.insert("workspace", record.workspace_path.clone());
+        node.insert("tab", record.tab_name.clone());
+        if !record.command.is_empty() {
+            let mut children = KdlDocument::new();
+            let mut cmd = KdlNode::new("cmd");
+            for part in &record.command {
+                cmd.push(part.clone());
+            }
+            children.nodes_mut().push(cmd);
+            node.set_children(children);
+        }
+        doc.nodes_mut().push(node);
+    }
+    doc.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "4f8c2a8e-1d7b-4b8e-9a51-3c2f0e6d7b10";
+    const OTHER_ID: &str = "0b9d9c1e-7c55-4d1e-8f3a-2a6b5c4d3e21";
+
+    fn record(id: &str, pane_id: Option<u32>) -> PaneRecord {
+        PaneRecord {
+            id: id.to_string(),
+            session: "work".to_string(),
+            pane_id,
+            agent_name: "claude".to_string(),
+            workspace_path: "/host/src/app".to_string(),
+            tab_name: "app".to_string(),
+            command: vec![
+                "claude".to_string(),
+                "--model".to_string(),
+                "opus 4".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_pane_uuid() {
+        assert_eq!(pane_uuid(&format!("app:{ID}")), Some(ID));
+        assert_eq!(pane_uuid(&format!("a:b:{ID}")), Some(ID));
+        assert_eq!(pane_uuid("claude --model opus"), None);
+        assert_eq!(pane_uuid("app:not-a-uuid"), None);
+    }
+
+    #[test]
+    fn test_lookup_and_bind() {
+        let mut registry = PaneRegistry::default();
+        registry.insert(record(ID, None));
+        assert!(registry.take_dirty());
+
+        assert_eq!(
+            registry
+                .find_by_title(&format!("app:{ID}"))
+                .map(|r| r.id.as_str()),
+            Some(ID)
+        );
+        assert!(registry.find_by_pane_id("work", 3).is_none());
+
+        registry.bind_pane_id(ID, "work", 3);
+        assert!(registry.take_dirty());
+        assert_eq!(
+            registry.find_by_pane_id("work", 3).map(|r| r.id.as_str()),
+            Some(ID)
+        );
+        assert!(registry.find_by_pane_id("other", 3).is_none());
+
+        // Rebinding the same id is not a change.
+        registry.bind_pane_id(ID, "work", 3);
+        assert!(!registry.take_dirty());
+
+        registry.set_tab("work", 3, "other");
+        assert!(registry.take_dirty());
+        assert_eq!(registry.records()[0].tab_name, "other");
+    }
+
+    #[test]
+    fn test_find_by_command() {
+        let mut registry = PaneRegistry::default();
+        registry.insert(record(ID, Some(3)));
+        let none_live = |_: u32| false;
+
+        assert_eq!(
+            registry
+                .find_by_command("work", "claude --model opus 4 --continue", none_live)
+                .map(|r| r.id.as_str()),
+            Some(ID)
+        );
+        assert!(registry
+            .find_by_command("work", "claude --model sonnet", none_live)
+            .is_none());
+        assert!(registry
+            .find_by_command("other", "claude --model opus 4", none_live)
+            .is_none());
+        // A record still bound to a live pane belongs to that pane.
+        assert!(registry
+            .find_by_command("work", "claude --model opus 4", |id| id == 3)
+            .is_none());
+    }
+
+    #[test]
+    fn test_bind_clears_reused_pane_id() {
+        let mut registry = PaneRegistry::default();
+        registry.insert(record(ID, Some(3)));
+        registry.insert(record(OTHER_ID, None));
+
+        registry.bind_pane_id(OTHER_ID, "work", 3);
+        assert_eq!(
+            registry.find_by_pane_id("work", 3).map(|r| r.id.as_str()),
+            Some(OTHER_ID)
+        );
+        assert_eq!(registry.records()[0].pane_id, None);
+    }
+
+    #[test]
+    fn test_remove_pane() {
+        let mut registry = PaneRegistry::default();
+        registry.insert(record(ID, Some(3)));
+        registry.take_dirty();
+
+        registry.remove_pane("other", 3);
+        assert!(!registry.take_dirty());
+        registry.remove_pane("work", 3);
+        assert!(registry.take_dirty());
+        assert!(registry.records().is_empty());
+    }
+
+    #[test]
+    fn test_session_records_and_pruning() {
+        let mut registry = PaneRegistry::default();
+        registry.insert(record(ID, Some(1)));
+        let mut elsewhere = record(OTHER_ID, Some(1));
+        elsewhere.session = "gone".to_string();
+        registry.insert(elsewhere);
+        let mut unknown = record("9a1f7e52-3b0c-4d8e-a6f2-5c9b1d3e7f40", None);
+        unknown.session.clear();
+        registry.insert(unknown);
+        registry.take_dirty();
+
+        assert_eq!(registry.session_records("gone").len(), 1);
+        registry.retain_sessions(|name| name == "work");
+        assert!(registry.take_dirty());
+        assert!(registry.session_records("gone").is_empty());
+        assert_eq!(registry.records().len(), 2);
+
+        registry.remove_session("work");
+        assert_eq!(registry.records().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("maestro").join("panes.kdl");
+        let mut registry = PaneRegistry::default();
+        registry.insert(record(ID, Some(7)));
+        registry.insert(record(OTHER_ID, None));
+        registry.take_dirty();
+
+        save_registry(&path, &registry).unwrap();
+        assert_eq!(load_registry(&path).unwrap(), registry);
+    }
+
+    #[test]
+    fn test_load_missing_registry_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = load_registry(&dir.path().join("panes.kdl")).unwrap();
+        assert!(registry.records().is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+//! Importing and exporting agent definitions as standalone files.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{
+    agents_to_kdl, names_match, parse_agents_kdl, unique_agent_name, validate_agents, Agent,
+};
+use crate::error::{MaestroError, MaestroResult};
+
+/// File format of a shared agent definition file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShareFormat {
+    /// Same `agent` node schema as `agents.kdl`.
+    #[default]
+    Kdl,
+    /// A list of agents, or an object with an `agents` list.
+    Json,
+    /// `[[agent]]` tables.
+    Toml,
+}
+
+impl ShareFormat {
+    /// Pick a format from the file extension, defaulting to KDL.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .as_deref()
+        {
+            Some("json") => ShareFormat::Json,
+            Some("toml") => ShareFormat::Toml,
+            _ => ShareFormat::Kdl,
+        }
+    }
+}
+
+/// How to handle an imported agent whose name is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Keep the existing agent and drop the imported one.
+    #[default]
+    Skip,
+    /// Add the imported agent under a fresh name.
+    Rename,
+    /// Replace the existing agent with the imported one.
+    Overwrite,
+}
+
+impl ConflictStrategy {
+    /// Cycle to the next strategy.
+    pub fn next(self) -> Self {
+        match self {
+            ConflictStrategy::Skip => ConflictStrategy::Rename,
+            ConflictStrategy::Rename => ConflictStrategy::Overwrite,
+            ConflictStrategy::Overwrite => ConflictStrategy::Skip,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictStrategy::Skip => "skip",
+            ConflictStrategy::Rename => "rename",
+            ConflictStrategy::Overwrite => "overwrite",
+        }
+    }
+}
+
+/// Outcome of merging imported agents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: Vec<String>,
+    /// Pairs of (original name, name it was imported as).
+    pub renamed: Vec<(String, String)>,
+    pub overwritten: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl ImportSummary {
+    /// One-line description for the status bar.
+    pub fn describe(&self) -> String {
+        let imported = self.added.len() + self.renamed.len() + self.overwritten.len();
+        let mut details = Vec::new();
+        if !self.renamed.is_empty() {
+            details.push(format!("{} renamed", self.renamed.len()));
+        }
+        if !self.overwritten.is_empty() {
+            details.push(format!("{} overwritten", self.overwritten.len()));
+        }
+        if !self.skipped.is_empty() {
+            details.push(format!("{} skipped", self.skipped.len()));
+        }
+        if details.is_empty() {
+            format!("Imported {imported} agent(s)")
+        } else {
+            format!("Imported {imported} agent(s) ({})", details.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TomlAgents {
+    #[serde(default, alias = "agents")]
+    agent: Vec<Agent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonAgents {
+    List(Vec<Agent>),
+    Wrapped { agents: Vec<Agent> },
+}
+
+/// Parse agent definitions from text in the given format.
+pub fn parse_agents(data: &str, format: ShareFormat) -> MaestroResult<Vec<Agent>> {
+    let agents = match format {
+        ShareFormat::Kdl => return parse_agents_kdl(data),
+        ShareFormat::Json => match serde_json::from_str::<JsonAgents>(data)
+            .map_err(|e| MaestroError::ConfigParse(e.to_string()))?
+        {
+            JsonAgents::List(agents) | JsonAgents::Wrapped { agents } => agents,
+        },
+        ShareFormat::Toml => {
+            toml::from_str::<TomlAgents>(data)
+                .map_err(|e| MaestroError::ConfigParse(e.to_string()))?
+                .agent
+        }
+    };
+    validate_agents(&agents)?;
+    Ok(agents)
+}
+
+/// Read agent definitions from a shared file, picking the format by extension.
+pub fn read_agents_file(path: &Path) -> MaestroResult<Vec<Agent>> {
+    let data = fs::read_to_string(path).map_err(|e| MaestroError::FileRead {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    let agents = parse_agents(&data, ShareFormat::from_path(path))?;
+    if agents.is_empty() {
+        return Err(MaestroError::NoAgentsFound(path.to_path_buf()));
+    }
+    Ok(agents)
+}
+
+/// Merge imported agents into `existing`, resolving name clashes with `strategy`.
+/// `existing` is left untouched if the merged list fails validation.
+pub fn merge_agents(
+    existing: &mut Vec<Agent>,
+    incoming: Vec<Agent>,
+    strategy: ConflictStrategy,
+) -> MaestroResult<ImportSummary> {
+    let mut merged = existing.clone();
+    let mut summary = ImportSummary::default();
+
+    for mut agent in incoming {
+        let Some(pos) = merged
+            .iter()
+            .position(|a| names_match(&a.name, &agent.name))
+        else {
+            summary.added.push(agent.name.clone());
+            merged.push(agent);
+            continue;
+        };
+        match strategy {
+            ConflictStrategy::Skip => summary.skipped.push(agent.name),
+            ConflictStrategy::Overwrite => {
+                summary.overwritten.push(agent.name.clone());
+                merged[pos] = agent;
+            }
+            ConflictStrategy::Rename => {
+                let new_name = unique_agent_name(&merged, &agent.name);
+                summary.renamed.push((agent.name.clone(), new_name.clone()));
+                agent.name = new_name;
+                merged.push(agent);
+            }
+        }
+    }
+
+    validate_agents(&merged)?;
+    *existing = merged;
+    Ok(summary)
+}
+
+/// Render agents as a standalone snippet in the given format.
+pub fn export_agents(agents: &[Agent], format: ShareFormat) -> MaestroResult<String> {
+    validate_agents(agents)?;
+    match format {
+        ShareFormat::Kdl => Ok(agents_to_kdl(agents)),
+        ShareFormat::Json => serde_json::to_string_pretty(agents)
+            .map(|mut s| {
+                s.push('\n');
+                s
+            })
+            .map_err(|e| MaestroError::ConfigParse(e.to_string())),
+        ShareFormat::Toml => toml::to_string(&TomlAgents {
+            agent: agents.to_vec(),
+        })
+        .map_err(|e| MaestroError::ConfigParse(e.to_string())),
+    }
+}
+
+/// Write agents to `path`, picking the format by extension.
+pub fn write_agents_file(path: &Path, agents: &[Agent]) -> MaestroResult<()> {
+    let payload = export_agents(agents, ShareFormat::from_path(path))?;
+    fs::write(path, payload.as_bytes()).map_err(|e| MaestroError::FileWrite {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_agent;
+
+    #[test]
+    fn test_share_format_from_path() {
+        assert_eq!(
+            ShareFormat::from_path(Path::new("a.json")),
+            ShareFormat::Json
+        );
+        assert_eq!(
+            ShareFormat::from_path(Path::new("a.TOML")),
+            ShareFormat::Toml
+        );
+        assert_eq!(ShareFormat::from_path(Path::new("a.kdl")), ShareFormat::Kdl);
+        assert_eq!(
+            ShareFormat::from_path(Path::new("agents")),
+            ShareFormat::Kdl
+        );
+    }
+
+    #[test]
+    fn test_export_and_parse_round_trip() {
+        let mut agent = create_test_agent("reviewer");
+        agent.args = vec!["--prompt".to_string(), "hello world".to_string()];
+        agent.note = Some("shared".to_string());
+        let agents = vec![agent, create_test_agent("other")];
+
+        for format in [ShareFormat::Kdl, ShareFormat::Json, ShareFormat::Toml] {
+            let text = export_agents(&agents, format).unwrap();
+            let parsed = parse_agents(&text, format).unwrap();
+            assert_eq!(parsed, agents, "round trip via {format:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_json_wrapped() {
+        let data = r#"{"agents": [{"name": "a", "command": "echo"}]}"#;
+        let parsed = parse_agents(data, ShareFormat::Json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].command, "echo");
+    }
+
+    #[test]
+    fn test_parse_validates_agents() {
+        let data = r#"[{"name": "a", "command": "echo"}, {"name": "A", "command": "ls"}]"#;
+        assert!(matches!(
+            parse_agents(data, ShareFormat::Json),
+            Err(MaestroError::DuplicateAgentName(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge_agents_strategies() {
+        let existing = vec![create_test_agent("claude")];
+        let mut incoming_claude = create_test_agent("Claude");
+        incoming_claude.command = "claude-beta".to_string();
+        let incoming = vec![incoming_claude, create_test_agent("codex")];
+
+        let mut skipped = existing.clone();
+        let summary = merge_agents(&mut skipped, incoming.clone(), ConflictStrategy::Skip).unwrap();
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].command, "echo");
+        assert_eq!(summary.skipped, vec!["Claude".to_string()]);
+        assert_eq!(summary.added, vec!["codex".to_string()]);
+
+        let mut overwritten = existing.clone();
+        merge_agents(
+            &mut overwritten,
+            incoming.clone(),
+            ConflictStrategy::Overwrite,
+        )
+        .unwrap();
+        assert_eq!(overwritten.len(), 2);
+        assert_eq!(overwritten[0].command, "claude-beta");
+
+        let mut renamed = existing.clone();
+        let summary = merge_agents(&mut renamed, incoming, ConflictStrategy::Rename).unwrap();
+        assert_eq!(renamed.len(), 3);
+        assert!(renamed.iter().any(|a| a.name == "Claude-2"));
+        assert_eq!(
+            summary.renamed,
+            vec![("Claude".to_string(), "Claude-2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_read_agents_file_empty_is_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agents.kdl");
+        fs::write(&path, "").unwrap();
+        assert!(matches!(
+            read_agents_file(&path),
+            Err(MaestroError::NoAgentsFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_and_read_agents_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agents.toml");
+        let agents = vec![create_test_agent("shared")];
+        write_agents_file(&path, &agents).unwrap();
+        assert_eq!(read_agents_file(&path).unwrap(), agents);
+    }
+
+    #[test]
+    fn test_import_summary_describe() {
+        let summary = ImportSummary {
+            added: vec!["a".to_string()],
+            renamed: vec![("b".to_string(), "b-2".to_string())],
+            overwritten: Vec::new(),
+            skipped: vec!["c".to_string()],
+        };
+        assert_eq!(
+            summary.describe(),
+            "Imported 2 agent(s) (1 renamed, 1 skipped)"
+        );
+    }
+}
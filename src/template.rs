@@ -0,0 +1,459 @@
+//! Placeholder expansion for agent commands and arguments.
+//!
+//! Arguments may contain `{name}` placeholders filled in at spawn time, and
+//! prompted `{?name}` variables the new-pane wizard asks for. A prompted
+//! variable may carry a default (`{?model=sonnet}`) or a list of choices
+//! separated by `|` (`{?model=sonnet|opus}`, first choice is the default).
+//! `{{` and `}}` produce literal braces.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{MaestroError, MaestroResult};
+
+/// Placeholders filled from the spawn context.
+pub const BUILTIN_PLACEHOLDERS: &[&str] = &[
+    "workspace",
+    "workspace_name",
+    "tab",
+    "session",
+    "branch",
+    "date",
+];
+
+/// A variable the user is asked for before spawning.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PromptVar {
+    pub name: String,
+    pub default: Option<String>,
+    pub choices: Vec<String>,
+}
+
+/// Values available to placeholders when an agent is spawned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateContext {
+    pub workspace: String,
+    pub workspace_name: String,
+    pub tab: String,
+    pub session: String,
+    pub branch: String,
+    pub date: String,
+    /// Values for prompted `{?name}` variables.
+    pub prompted: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    /// Made-up values for showing what a command expands to before it is
+    /// spawned anywhere.
+    pub fn sample() -> Self {
+        Self {
+            workspace: "/home/you/project".to_string(),
+            workspace_name: "project".to_string(),
+            tab: "project".to_string(),
+            session: "main".to_string(),
+            branch: "main".to_string(),
+            date: today(),
+            prompted: BTreeMap::new(),
+        }
+    }
+
+    fn builtin(&self, name: &str) -> Option<&str> {
+        match name {
+            "workspace" => Some(&self.workspace),
+            "workspace_name" => Some(&self.workspace_name),
+            "tab" => Some(&self.tab),
+            "session" => Some(&self.session),
+            "branch" => Some(&self.branch),
+            "date" => Some(&self.date),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    /// Raw placeholder text between the braces, e.g. `workspace` or `?model=a|b`.
+    Placeholder(&'a str),
+}
+
+fn segments(input: &str) -> MaestroResult<Vec<Segment<'_>>> {
+    let mut out = Vec::new();
+    let mut rest = input;
+    while let Some(pos) = rest.find(['{', '}']) {
+        if pos > 0 {
+            out.push(Segment::Literal(&rest[..pos]));
+        }
+        let tail = &rest[pos..];
+        if let Some(after) = tail.strip_prefix("{{") {
+            out.push(Segment::Literal("{"));
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("}}") {
+            out.push(Segment::Literal("}"));
+            rest = after;
+        } else if tail.starts_with('}') {
+            return Err(MaestroError::InvalidTemplate(format!(
+                "unmatched '}}' in {input}"
+            )));
+        } else {
+            let end = tail.find('}').ok_or_else(|| {
+                MaestroError::InvalidTemplate(format!("unterminated '{{' in {input}"))
+            })?;
+            out.push(Segment::Placeholder(&tail[1..end]));
+            rest = &tail[end + 1..];
+        }
+    }
+    if !rest.is_empty() {
+        out.push(Segment::Literal(rest));
+    }
+    Ok(out)
+}
+
+fn parse_prompt(raw: &str) -> Option<PromptVar> {
+    let body = raw.strip_prefix('?')?;
+    let (name, spec) = match body.split_once('=') {
+        Some((name, spec)) => (name.trim(), Some(spec)),
+        None => (body.trim(), None),
+    };
+    let choices: Vec<String> = spec
+        .filter(|s| s.contains('|'))
+        .map(|s| s.split('|').map(|c| c.trim().to_string()).collect())
+        .unwrap_or_default();
+    let default = match spec {
+        Some(_) if !choices.is_empty() => choices.first().cloned(),
+        Some(s) => Some(s.to_string()),
+        None => None,
+    };
+    Some(PromptVar {
+        name: name.to_string(),
+        default,
+        choices,
+    })
+}
+
+fn is_valid_var_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Check that every placeholder in the command and args is known and well-formed.
+pub fn validate_templates<'a>(parts: impl IntoIterator<Item = &'a String>) -> MaestroResult<()> {
+    for part in parts {
+        for segment in segments(part)? {
+            let Segment::Placeholder(raw) = segment else {
+                continue;
+            };
+            let known = match parse_prompt(raw) {
+                Some(var) => is_valid_var_name(&var.name),
+                None => BUILTIN_PLACEHOLDERS.contains(&raw.trim()),
+            };
+            if !known {
+                return Err(MaestroError::UnknownPlaceholder(format!("{{{raw}}}")));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collect the prompted variables a command needs, in first-use order.
+/// Malformed templates contribute no variables.
+pub fn prompted_variables(parts: &[String]) -> Vec<PromptVar> {
+    let mut vars: Vec<PromptVar> = Vec::new();
+    for part in parts {
+        let Ok(parsed) = segments(part) else {
+            continue;
+        };
+        for segment in parsed {
+            if let Segment::Placeholder(raw) = segment {
+                if let Some(var) = parse_prompt(raw) {
+                    if is_valid_var_name(&var.name) && !vars.iter().any(|v| v.name == var.name) {
+                        vars.push(var);
+                    }
+                }
+            }
+        }
+    }
+    vars
+}
+
+/// Expand placeholders in one string. Unknown or malformed placeholders are
+/// left verbatim so hand-written configs predating templates keep working.
+pub fn expand(input: &str, ctx: &TemplateContext) -> String {
+    let Ok(parsed) = segments(input) else {
+        return input.to_string();
+    };
+    let mut out = String::new();
+    for segment in parsed {
+        match segment {
+            Segment::Literal(text) => out.push_str(text),
+            Segment::Placeholder(raw) => {
+                let value = match parse_prompt(raw) {
+                    Some(var) => ctx.prompted.get(&var.name).cloned().or(var.default),
+                    None => ctx.builtin(raw.trim()).map(str::to_string),
+                };
+                match value {
+                    Some(v) => out.push_str(&v),
+                    None => {
+                        out.push('{');
+                        out.push_str(raw);
+                        out.push('}');
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Expand placeholders in every part of a command line.
+pub fn expand_command(parts: &[String], ctx: &TemplateContext) -> Vec<String> {
+    parts.iter().map(|part| expand(part, ctx)).collect()
+}
+
+/// Checked-out git branches of workspaces. Lookups run `git` in the
+/// background so spawning never waits on the filesystem: a workspace is
+/// queued when it is chosen (see [`BranchCache::take_requests`]) and its
+/// branch is known once [`BranchCache::fill`] delivers it.
+#[derive(Debug, Clone, Default)]
+pub struct BranchCache {
+    branches: BTreeMap<PathBuf, String>,
+    /// Directories being looked up, so a lookup is started only once.
+    loading: BTreeSet<PathBuf>,
+    /// Lookups not yet handed to the host.
+    requests: Vec<PathBuf>,
+}
+
+impl BranchCache {
+    /// Queue a lookup of the branch of `dir`, refreshing a known one.
+    pub fn request(&mut self, dir: &Path) {
+        if self.loading.insert(dir.to_path_buf()) {
+            self.requests.push(dir.to_path_buf());
+        }
+    }
+
+    /// Drain the queued lookups for the host to start.
+    pub fn take_requests(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.requests)
+    }
+
+    /// Store the result of a lookup; `None` for a detached head or a
+    /// directory outside any repository.
+    pub fn fill(&mut self, dir: &Path, branch: Option<String>) {
+        self.loading.remove(dir);
+        match branch {
+            Some(branch) => self.branches.insert(dir.to_path_buf(), branch),
+            None => self.branches.remove(dir),
+        };
+    }
+
+    /// The last branch seen for `dir`.
+    pub fn get(&self, dir: &Path) -> Option<&str> {
+        self.branches.get(dir).map(String::as_str)
+    }
+}
+
+/// The branch printed by `git symbolic-ref --short -q HEAD`, which fails
+/// for detached heads and outside repositories.
+pub fn parse_branch(exit_code: Option<i32>, stdout: &[u8]) -> Option<String> {
+    if exit_code != Some(0) {
+        return None;
+    }
+    let branch = String::from_utf8_lossy(stdout).trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_date(secs)
+}
+
+/// Format a Unix timestamp as a `YYYY-MM-DD` UTC date.
+pub fn format_date(unix_secs: u64) -> String {
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_agent;
+    use crate::utils::build_command;
+
+    fn ctx() -> TemplateContext {
+        TemplateContext {
+            workspace: "/home/me/src/app".to_string(),
+            workspace_name: "app".to_string(),
+            tab: "app".to_string(),
+            session: "work".to_string(),
+            branch: "main".to_string(),
+            date: "2025-01-02".to_string(),
+            prompted: BTreeMap::new(),
+        }
+    }
+
+    fn strings(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expand_builtins() {
+        assert_eq!(
+            expand("--log={workspace_name}-{branch}-{date}", &ctx()),
+            "--log=app-main-2025-01-02"
+        );
+        assert_eq!(expand("{workspace}", &ctx()), "/home/me/src/app");
+        assert_eq!(expand("{session}/{tab}", &ctx()), "work/app");
+    }
+
+    #[test]
+    fn test_expand_escapes_and_unknown() {
+        assert_eq!(expand("{{literal}}", &ctx()), "{literal}");
+        assert_eq!(expand("{unknown}", &ctx()), "{unknown}");
+        assert_eq!(expand("{\"a\":1", &ctx()), "{\"a\":1");
+    }
+
+    #[test]
+    fn test_expand_prompted() {
+        let mut ctx = ctx();
+        assert_eq!(expand("{?model=sonnet}", &ctx), "sonnet");
+        assert_eq!(expand("{?model=opus|sonnet}", &ctx), "opus");
+        assert_eq!(expand("{?model}", &ctx), "{?model}");
+        ctx.prompted
+            .insert("model".to_string(), "haiku".to_string());
+        assert_eq!(expand("--model={?model=sonnet}", &ctx), "--model=haiku");
+    }
+
+    #[test]
+    fn test_validate_templates() {
+        assert!(validate_templates(&strings(&["--cwd", "{workspace}", "{?model=a|b}"])).is_ok());
+        assert!(validate_templates(&strings(&["{{not a placeholder}}"])).is_ok());
+        assert!(matches!(
+            validate_templates(&strings(&["{workspce}"])),
+            Err(MaestroError::UnknownPlaceholder(p)) if p == "{workspce}"
+        ));
+        assert!(matches!(
+            validate_templates(&strings(&["{?bad name}"])),
+            Err(MaestroError::UnknownPlaceholder(_))
+        ));
+        assert!(matches!(
+            validate_templates(&strings(&["{workspace"])),
+            Err(MaestroError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            validate_templates(&strings(&["oops}"])),
+            Err(MaestroError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_prompted_variables() {
+        let mut agent = create_test_agent("claude");
+        agent.args = strings(&[
+            "--model",
+            "{?model=opus|sonnet}",
+            "--name={?label}",
+            "{?model}",
+            "{workspace}",
+        ]);
+        let vars = prompted_variables(&build_command(&agent));
+        assert_eq!(
+            vars,
+            vec![
+                PromptVar {
+                    name: "model".to_string(),
+                    default: Some("opus".to_string()),
+                    choices: strings(&["opus", "sonnet"]),
+                },
+                PromptVar {
+                    name: "label".to_string(),
+                    default: None,
+                    choices: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_command() {
+        let mut agent = create_test_agent("claude");
+        agent.args = strings(&["--dir", "{workspace}"]);
+        assert_eq!(
+            expand_command(&build_command(&agent), &ctx()),
+            strings(&["echo", "--dir", "/home/me/src/app"])
+        );
+    }
+
+    #[test]
+    fn test_branch_cache() {
+        let mut cache = BranchCache::default();
+        let dir = Path::new("/host/app");
+        cache.request(dir);
+        cache.request(dir);
+        assert_eq!(cache.take_requests(), vec![dir.to_path_buf()]);
+        assert_eq!(cache.get(dir), None);
+
+        cache.fill(dir, Some("main".to_string()));
+        assert_eq!(cache.get(dir), Some("main"));
+
+        // A refresh keeps the known branch until it answers.
+        cache.request(dir);
+        assert_eq!(cache.take_requests().len(), 1);
+        assert_eq!(cache.get(dir), Some("main"));
+        cache.fill(dir, None);
+        assert_eq!(cache.get(dir), None);
+    }
+
+    #[test]
+    fn test_parse_branch() {
+        assert_eq!(
+            parse_branch(Some(0), b"feature/x\n"),
+            Some("feature/x".to_string())
+        );
+        assert_eq!(parse_branch(Some(1), b""), None);
+        assert_eq!(parse_branch(Some(128), b""), None);
+        assert_eq!(parse_branch(None, b"main\n"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_735_776_000), "2025-01-02");
+    }
+
+    #[test]
+    fn test_format_date_leap_years() {
+        // 2024 is a leap year: Feb 29 exists and Mar 1 follows it.
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+        assert_eq!(format_date(1_709_251_200), "2024-03-01");
+        // 2023 is not: Feb 28 is followed by Mar 1.
+        assert_eq!(format_date(1_677_542_400), "2023-02-28");
+        assert_eq!(format_date(1_677_628_800), "2023-03-01");
+        assert_eq!(format_date(1_704_067_199), "2023-12-31");
+        assert_eq!(format_date(1_735_603_200), "2024-12-31");
+    }
+
+    #[test]
+    fn test_format_date_century_boundaries() {
+        // 2000 is a leap year (divisible by 400), 2100 is not.
+        assert_eq!(format_date(946_684_799), "1999-12-31");
+        assert_eq!(format_date(946_684_800), "2000-01-01");
+        assert_eq!(format_date(4_107_456_000), "2100-02-28");
+        assert_eq!(format_date(4_107_542_400), "2100-03-01");
+        assert_eq!(format_date(4_133_980_799), "2100-12-31");
+        assert_eq!(format_date(4_133_980_800), "2101-01-01");
+    }
+}
@@ -1,16 +1,25 @@
 //! UI rendering and mode definitions.
 
-use zellij_tile::ui_components::{serialize_table, Table, Text};
+use zellij_tile::ui_components::{serialize_table, serialize_text, Table, Text};
 
-use crate::agent::PaneStatus;
+use crate::agent::{AgentPane, Availability, PaneStatus};
+use crate::input::TextInput;
+use crate::keymap::Action;
 use crate::model::Model;
-use crate::utils::truncate;
+use crate::template::TemplateContext;
+use crate::utils::{truncate, WorkspaceSuggestion};
 use crate::WASI_HOST_MOUNT;
 
 // Zellij terminal palette indices
 const COLOR_GREEN: usize = 2;
 const COLOR_RED: usize = 1;
-const MAX_SUGGESTIONS_DISPLAYED: usize = 5;
+const COLOR_MATCH: usize = 3;
+const COLOR_CURSOR: usize = 0;
+pub const MAX_SUGGESTIONS_DISPLAYED: usize = 5;
+const MISSING_MARKER: &str = "(not found)";
+/// Lines of the help screen besides its entries: title, blank line, table
+/// header and status line.
+const HELP_CHROME_ROWS: usize = 4;
 
 /// The current UI mode/screen.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -18,18 +27,48 @@ pub enum Mode {
     /// Main view showing running agent panes.
     #[default]
     View,
+    /// Agent panes of every session, grouped by session.
+    AllSessions,
+    /// Tab picker for moving the selected agent pane.
+    MovePaneTab,
     /// Agent configuration list.
     AgentConfig,
     /// Workspace path input for new pane wizard.
     NewPaneWorkspace,
+    /// Tab choice for new pane wizard.
+    NewPaneTabSelect,
     /// Agent selection for new pane wizard.
     NewPaneAgentSelect,
+    /// Prompted template variables for new pane wizard.
+    NewPanePrompt,
     /// Creating a new agent.
     AgentFormCreate,
     /// Editing an existing agent.
     AgentFormEdit,
     /// Confirming agent deletion.
     DeleteConfirm,
+    /// Path prompt for importing agents from a shared file.
+    AgentImport,
+    /// Path prompt for exporting agents to a shared file.
+    AgentExport,
+    /// Keys of the screen the help was opened from.
+    Help,
+}
+
+impl Mode {
+    /// Whether the screen has a text input, which takes typed characters.
+    pub fn takes_text(self) -> bool {
+        matches!(
+            self,
+            Mode::NewPaneWorkspace
+                | Mode::NewPaneTabSelect
+                | Mode::NewPanePrompt
+                | Mode::AgentFormCreate
+                | Mode::AgentFormEdit
+                | Mode::AgentImport
+                | Mode::AgentExport
+        )
+    }
 }
 
 /// Form field currently focused in agent create/edit.
@@ -62,6 +101,46 @@ pub fn prev_field(current: AgentFormField) -> AgentFormField {
     }
 }
 
+/// A workspace suggestion line: the path without the `/host/` prefix,
+/// tagged when it is a git repository or has running agents. Returns the
+/// line and the char indices of the matched characters in it.
+fn format_suggestion(
+    suggestion: &WorkspaceSuggestion,
+    selected: bool,
+    running: usize,
+    cols: usize,
+) -> (String, Vec<usize>) {
+    let host_prefix = format!("{}/", WASI_HOST_MOUNT);
+    let (path, skipped) = match suggestion.path.strip_prefix(&host_prefix) {
+        Some(rest) => (rest, host_prefix.chars().count()),
+        None => (suggestion.path.as_str(), 0),
+    };
+    let mut tags = String::new();
+    if suggestion.git == Some(true) {
+        tags.push_str(" [git]");
+    }
+    if running > 0 {
+        tags.push_str(&format!(" [{running} running]"));
+    }
+    let prefix = if selected { "> " } else { "  " };
+    let width = cols.saturating_sub(prefix.len() + tags.chars().count());
+    let shown = truncate(path, width);
+    // A truncated path ends in an ellipsis that covers the rest.
+    let visible = if shown.chars().count() < path.chars().count() {
+        shown.chars().count().saturating_sub(1)
+    } else {
+        shown.chars().count()
+    };
+    let matched = suggestion
+        .matched
+        .iter()
+        .filter_map(|i| i.checked_sub(skipped))
+        .filter(|i| *i < visible)
+        .map(|i| i + prefix.len())
+        .collect();
+    (format!("{prefix}{shown}{tags}"), matched)
+}
+
 /// Render the permissions denied screen.
 pub fn render_permissions_denied(rows: usize, cols: usize) -> String {
     format!(
@@ -78,6 +157,13 @@ pub fn render_permissions_requesting(rows: usize, cols: usize) -> String {
 pub fn render_ui(model: &Model, cols: usize) -> String {
     let mut out = String::new();
 
+    if model.mode == Mode::Help {
+        out.push_str(&render_help(model, cols));
+        out.push('\n');
+        out.push_str(&render_status(model, cols));
+        return out;
+    }
+
     out.push_str(&render_agent_panes(model, cols));
 
     if let Some(overlay) = render_overlay(model, cols) {
@@ -89,16 +175,85 @@ pub fn render_ui(model: &Model, cols: usize) -> String {
     out
 }
 
+/// How many help entries fit in a plugin pane of `rows` lines.
+pub fn help_visible_entries(rows: usize) -> usize {
+    rows.saturating_sub(HELP_CHROME_ROWS).max(1)
+}
+
+/// Screen name shown in the help title.
+pub fn screen_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::View => "Agent panes",
+        Mode::AllSessions => "All sessions",
+        Mode::MovePaneTab => "Move pane",
+        Mode::AgentConfig => "Agent configuration",
+        Mode::NewPaneWorkspace => "New pane: workspace",
+        Mode::NewPaneTabSelect => "New pane: tab",
+        Mode::NewPaneAgentSelect => "New pane: agent",
+        Mode::NewPanePrompt => "New pane: template values",
+        Mode::AgentFormCreate => "Add agent",
+        Mode::AgentFormEdit => "Edit agent",
+        Mode::DeleteConfirm => "Delete agent",
+        Mode::AgentImport => "Import agents",
+        Mode::AgentExport => "Export agents",
+        Mode::Help => "Help",
+    }
+}
+
+/// The keys of the screen the help was opened from, scrolled to
+/// `model.help.scroll` and clipped to the pane height.
+fn render_help(model: &Model, cols: usize) -> String {
+    let entries = model.config.keymap.help_entries(model.help.mode);
+    let visible = help_visible_entries(model.rows);
+    let start = model.help.scroll.min(entries.len().saturating_sub(visible));
+    let end = (start + visible).min(entries.len());
+
+    let mut title = format!("Help: {}", screen_name(model.help.mode));
+    if start > 0 || end < entries.len() {
+        title.push_str(&format!(" ({}-{end} of {})", start + 1, entries.len()));
+    }
+
+    let key_width = entries
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(cols / 3);
+    let help_width = cols.saturating_sub(key_width + 1);
+    let mut table = Table::new().add_row(vec!["Key", "Action"]);
+    for (keys, help) in &entries[start..end] {
+        table = table.add_row(vec![truncate(keys, key_width), truncate(help, help_width)]);
+    }
+    [
+        truncate(&title, cols),
+        String::new(),
+        serialize_table(&table),
+    ]
+    .join("\n")
+}
+
+/// Agent column text, listing the candidates of an ambiguously matched pane.
+fn pane_agent_label(pane: &AgentPane) -> String {
+    if !pane.agent_name.is_empty() {
+        pane.agent_name.clone()
+    } else if !pane.candidates.is_empty() {
+        format!("{}?", pane.candidates.join(" | "))
+    } else {
+        "(agent)".to_string()
+    }
+}
+
 fn render_agent_panes(model: &Model, cols: usize) -> String {
     let mut table = Table::new().add_row(vec!["Tab", "Agent", "Status"]);
 
     for (idx, pane) in model.agent_panes.iter().enumerate() {
         let tab = truncate(&pane.tab_name, cols.saturating_sub(20));
-        let agent = if pane.agent_name.is_empty() {
-            "(agent)"
-        } else {
-            &pane.agent_name
-        };
+        let mut agent = pane_agent_label(pane);
+        if pane.pinned {
+            agent.push_str(" (floating, pinned)");
+        } else if pane.floating {
+            agent.push_str(" (floating)");
+        }
         let status_text = match pane.status {
             PaneStatus::Running => "RUNNING",
             PaneStatus::Exited(_) => "EXITED",
@@ -111,7 +266,7 @@ fn render_agent_panes(model: &Model, cols: usize) -> String {
 
         let mut row = vec![
             Text::new(tab),
-            Text::new(agent.to_string()),
+            Text::new(agent),
             Text::new(status_text.to_string()).color_all(status_color),
         ];
 
@@ -131,19 +286,101 @@ fn render_agent_panes(model: &Model, cols: usize) -> String {
     serialize_table(&table)
 }
 
+/// Agent panes of all sessions; the session name is shown on the first row of each group.
+fn render_all_sessions(model: &Model, cols: usize) -> String {
+    let mut table = Table::new().add_row(vec!["Session", "Tab", "Agent", "Status"]);
+    let current = model.session_name.as_deref().unwrap_or_default();
+    let mut previous: Option<&str> = None;
+
+    for (idx, (session, pane)) in model.all_session_panes().into_iter().enumerate() {
+        let session_label = if previous == Some(session) {
+            String::new()
+        } else if session == current {
+            format!("{} (current)", truncate(session, cols / 4))
+        } else {
+            truncate(session, cols / 4)
+        };
+        previous = Some(session);
+
+        let (status_text, status_color) = match pane.status {
+            PaneStatus::Running => ("RUNNING", COLOR_GREEN),
+            PaneStatus::Exited(_) => ("EXITED", COLOR_RED),
+        };
+        let mut row = vec![
+            Text::new(session_label),
+            Text::new(truncate(&pane.tab_name, cols / 4)),
+            Text::new(pane_agent_label(pane)),
+            Text::new(status_text.to_string()).color_all(status_color),
+        ];
+        if idx == model.selected_session_pane {
+            row = row.into_iter().map(|t| t.selected()).collect();
+        }
+        table = table.add_styled_row(row);
+    }
+
+    let live_len = model.all_session_panes().len();
+    for (offset, session) in model.resurrectable_sessions.iter().enumerate() {
+        let agents: Vec<&str> = model
+            .registry
+            .session_records(session)
+            .iter()
+            .map(|r| r.agent_name.as_str())
+            .collect();
+        let mut row = vec![
+            Text::new(format!("{} (exited)", truncate(session, cols / 4))),
+            Text::new(String::new()),
+            Text::new(truncate(&agents.join(", "), cols / 3)),
+            Text::new("RESURRECTABLE".to_string()).color_all(COLOR_RED),
+        ];
+        if live_len + offset == model.selected_session_pane {
+            row = row.into_iter().map(|t| t.selected()).collect();
+        }
+        table = table.add_styled_row(row);
+    }
+
+    if live_len == 0 && model.resurrectable_sessions.is_empty() {
+        table = table.add_row(vec![
+            "(no agent panes in any session)".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+        ]);
+    }
+    serialize_table(&table)
+}
+
 fn render_agent_management(model: &Model, cols: usize) -> String {
     let mut table = Table::new().add_row(vec!["Agent", "Command", "Note"]);
 
     let command_col_width = (cols as f32 * 0.50) as usize;
 
     for (idx, agent) in model.agents.iter().enumerate() {
-        let name = if agent.name.is_empty() {
-            "(agent)"
+        let mut name = if agent.name.is_empty() {
+            "(agent)".to_string()
+        } else if agent.hidden {
+            format!("{} (hidden)", agent.name)
         } else {
-            &agent.name
+            agent.name.clone()
         };
+        if !agent.variants.is_empty() {
+            name = format!("{name} [+{} variants]", agent.variants.len());
+        }
+        if model.marked_agents.contains(&agent.name.to_lowercase()) {
+            name = format!("* {name}");
+        }
         let command_full = shell_words::join(std::iter::once(&agent.command).chain(&agent.args));
-        let command = truncate(&command_full, command_col_width);
+        let missing = model.agent_availability(agent) == Availability::Missing;
+        let command = if missing {
+            format!(
+                "{} {MISSING_MARKER}",
+                truncate(
+                    &command_full,
+                    command_col_width.saturating_sub(MISSING_MARKER.len() + 1)
+                )
+            )
+        } else {
+            truncate(&command_full, command_col_width)
+        };
 
         let note = agent
             .note
@@ -151,13 +388,19 @@ fn render_agent_management(model: &Model, cols: usize) -> String {
             .filter(|n| !n.is_empty())
             .unwrap_or("—");
 
-        let row = vec![name.to_string(), command.to_string(), note.to_string()];
-        let styled = if idx == model.selected_agent {
-            row.into_iter().map(|c| Text::new(c).selected()).collect()
-        } else {
-            row.into_iter().map(Text::new).collect()
-        };
-        table = table.add_styled_row(styled);
+        let mut row = vec![
+            Text::new(name),
+            if missing {
+                Text::new(command).color_all(COLOR_RED)
+            } else {
+                Text::new(command)
+            },
+            Text::new(note),
+        ];
+        if idx == model.selected_agent {
+            row = row.into_iter().map(|t| t.selected()).collect();
+        }
+        table = table.add_styled_row(row);
     }
 
     if model.agents.is_empty() {
@@ -173,7 +416,41 @@ fn render_agent_management(model: &Model, cols: usize) -> String {
 
 fn render_overlay(model: &Model, cols: usize) -> Option<String> {
     match model.mode {
-        Mode::View => None,
+        Mode::View | Mode::Help => None,
+        Mode::AllSessions => {
+            let lines = [
+                "All Sessions".to_string(),
+                "".to_string(),
+                render_all_sessions(model, cols),
+            ];
+            Some(lines.join("\n"))
+        }
+        Mode::MovePaneTab => {
+            let pane = model.agent_panes.get(model.selected_pane)?;
+            let mut lines = vec![format!("Move {} to tab:", pane_agent_label(pane))];
+            let new_tab = format!(
+                "+ new tab \"{}\"",
+                crate::utils::default_tab_name(&pane.workspace_path)
+            );
+            let entries = model
+                .tab_names
+                .iter()
+                .map(String::as_str)
+                .chain([new_tab.as_str()]);
+            for (idx, name) in entries.enumerate() {
+                let prefix = if idx == model.move_tab_idx { ">" } else { " " };
+                let current = if name == pane.tab_name {
+                    " (current)"
+                } else {
+                    ""
+                };
+                lines.push(format!(
+                    "{prefix} {}{current}",
+                    truncate(name, cols.saturating_sub(12))
+                ));
+            }
+            Some(lines.join("\n"))
+        }
         Mode::AgentConfig => {
             let lines = [
                 "Agent Configuration".to_string(),
@@ -186,79 +463,161 @@ fn render_overlay(model: &Model, cols: usize) -> Option<String> {
             let mut lines = Vec::new();
             let input = &model.pane_wizard.workspace;
             let host_prefix = format!("{}/", WASI_HOST_MOUNT);
-            let display_input = input.strip_prefix(&host_prefix).unwrap_or(input);
             lines.push("New Agent Pane: workspace path".to_string());
-            lines.push(format!(
-                "> {}_",
-                truncate(display_input, cols.saturating_sub(2))
-            ));
+            lines.push(match input.strip_prefix(&host_prefix) {
+                Some(relative) => render_input_line("> ", &relative, cols),
+                None => render_input_line("> ", input, cols),
+            });
+            lines.push(match model.focused_workspace() {
+                Some(workspace) => format!(
+                    "Relative paths start at {}",
+                    crate::utils::display_workspace(workspace)
+                ),
+                None => "Relative paths start at the plugin cwd (focus an agent pane to use its workspace)"
+                    .to_string(),
+            });
+
+            let path_ctx = model.path_context();
+            let expanded = crate::utils::expand_workspace_input(input, &path_ctx);
+            if let Err(err) = crate::utils::check_visible(&expanded, &path_ctx) {
+                lines.push(err.to_string());
+            }
+            if !model.error_message.is_empty() {
+                let offer = if model.pane_wizard.create_workspace.is_some() {
+                    " (Enter again to create it)"
+                } else {
+                    ""
+                };
+                lines.push(format!("ERROR: {}{offer}", model.error_message));
+            }
 
-            if !input.trim().is_empty() {
-                let suggestions = crate::utils::get_path_suggestions(input);
-                if !suggestions.is_empty() {
-                    lines.push("".to_string());
-                    let max_display = MAX_SUGGESTIONS_DISPLAYED;
-                    let start_idx = if model.pane_wizard.browse_idx < max_display {
-                        0
-                    } else {
-                        model.pane_wizard.browse_idx.saturating_sub(max_display - 1)
-                    };
-                    let end_idx = (start_idx + max_display).min(suggestions.len());
-
-                    for (display_idx, suggestion) in
-                        suggestions[start_idx..end_idx].iter().enumerate()
-                    {
-                        let actual_idx = start_idx + display_idx;
-                        let prefix = if actual_idx == model.pane_wizard.browse_idx {
-                            ">"
-                        } else {
-                            " "
-                        };
-                        let display_path =
-                            suggestion.strip_prefix(&host_prefix).unwrap_or(suggestion);
-                        lines.push(format!(
-                            "{} {}",
-                            prefix,
-                            truncate(display_path, cols.saturating_sub(2))
-                        ));
-                    }
-
-                    if suggestions.len() > max_display {
-                        let showing = end_idx - start_idx;
-                        lines.push(format!("... showing {} of {}", showing, suggestions.len()));
-                    }
+            let suggestions = &model.pane_wizard.suggestions;
+            if model.pane_wizard.loading {
+                lines.push("Listing directory…".to_string());
+            }
+            if !suggestions.is_empty() {
+                lines.push("".to_string());
+                if input.trim().is_empty() {
+                    lines.push("Recent workspaces:".to_string());
+                }
+                let max_display = MAX_SUGGESTIONS_DISPLAYED;
+                let start_idx = if model.pane_wizard.browse_idx < max_display {
+                    0
+                } else {
+                    model.pane_wizard.browse_idx.saturating_sub(max_display - 1)
+                };
+                let end_idx = (start_idx + max_display).min(suggestions.len());
+
+                for (display_idx, suggestion) in suggestions[start_idx..end_idx].iter().enumerate()
+                {
+                    let actual_idx = start_idx + display_idx;
+                    let selected = actual_idx == model.pane_wizard.browse_idx;
+                    let running = model.running_agents_in(&suggestion.path);
+                    let (line, matched) = format_suggestion(suggestion, selected, running, cols);
+                    let text = Text::new(line).color_indices(COLOR_MATCH, matched);
+                    lines.push(serialize_text(&text));
+                }
+
+                if suggestions.len() > max_display {
+                    let showing = end_idx - start_idx;
+                    lines.push(format!("... showing {} of {}", showing, suggestions.len()));
                 }
             }
 
             Some(lines.join("\n"))
         }
+        Mode::NewPaneTabSelect => {
+            let wizard = &model.pane_wizard;
+            let mut lines = vec!["New Agent Pane: tab".to_string()];
+            let current = format!(
+                "current tab{}",
+                model
+                    .current_tab
+                    .as_ref()
+                    .map(|t| format!(" ({t})"))
+                    .unwrap_or_default()
+            );
+            let entries = [current.as_str()]
+                .into_iter()
+                .chain(model.tab_names.iter().map(String::as_str));
+            for (idx, name) in entries.enumerate() {
+                let prefix = if idx == wizard.tab_idx { ">" } else { " " };
+                lines.push(format!(
+                    "{prefix} {}",
+                    truncate(name, cols.saturating_sub(2))
+                ));
+            }
+            let prefix = if wizard.tab_idx == model.tab_names.len() + 1 {
+                ">"
+            } else {
+                " "
+            };
+            lines.push(render_input_line(
+                &format!("{prefix} + new tab: "),
+                &wizard.tab_input,
+                cols,
+            ));
+            Some(lines.join("\n"))
+        }
         Mode::NewPaneAgentSelect => {
             let mut lines = Vec::new();
 
             lines.push("Select agent:".to_string());
 
-            let filtered_indices = crate::utils::filter_agents_fuzzy(&model.agents, "");
+            let choices = crate::utils::filter_agent_choices(&model.agents, "");
+            let placement = match model.pane_wizard.placement {
+                Some(kind) => format!("{} ({})", kind.label(), kind.help()),
+                None => "agent default".to_string(),
+            };
+            lines.push(format!("Placement: {placement}"));
 
-            if filtered_indices.is_empty() {
+            if choices.is_empty() {
                 lines.push("  (no agents)".to_string());
             } else {
-                for (display_idx, &agent_idx) in filtered_indices.iter().enumerate() {
+                for (display_idx, choice) in choices.iter().enumerate() {
                     let prefix = if display_idx == model.pane_wizard.agent_idx {
                         ">"
                     } else {
                         " "
                     };
-                    let agent = &model.agents[agent_idx];
+                    let agent = &model.agents[choice.agent];
+                    let name = choice.label(&model.agents);
+                    let label = if model.agent_availability(agent) == Availability::Missing {
+                        format!("{name} {MISSING_MARKER}")
+                    } else {
+                        name
+                    };
                     lines.push(format!(
                         "{} {}",
                         prefix,
-                        truncate(&agent.name, cols.saturating_sub(2))
+                        truncate(&label, cols.saturating_sub(2))
                     ));
                 }
             }
 
             Some(lines.join("\n"))
         }
+        Mode::NewPanePrompt => {
+            let wizard = &model.pane_wizard;
+            let prompt = wizard.prompts.get(wizard.prompt_idx)?;
+            let mut lines = vec![format!(
+                "{} ({}/{}): {}",
+                wizard.agent_name.as_deref().unwrap_or("agent"),
+                wizard.prompt_idx + 1,
+                wizard.prompts.len(),
+                prompt.name
+            )];
+            lines.push(render_input_line("> ", &wizard.prompt_input, cols));
+            if !prompt.choices.is_empty() {
+                lines.push(truncate(
+                    &format!("Choices: {}", prompt.choices.join(", ")),
+                    cols,
+                ));
+            } else if let Some(default) = &prompt.default {
+                lines.push(truncate(&format!("Default: {default}"), cols));
+            }
+            Some(lines.join("\n"))
+        }
         Mode::AgentFormCreate => Some(render_agent_form_overlay(
             model,
             "Add agent (not yet persisted)",
@@ -280,61 +639,123 @@ fn render_overlay(model: &Model, cols: usize) -> Option<String> {
                 "Delete agent \"{name}\"? Enter/y to delete, Esc/n to cancel"
             ))
         }
+        Mode::AgentImport => {
+            let lines = [
+                "Import agents from file (.kdl, .json or .toml)".to_string(),
+                render_input_line("> ", &model.agent_transfer.path, cols),
+                format!(
+                    "On name conflict: {}",
+                    model.agent_transfer.strategy.label()
+                ),
+            ];
+            Some(lines.join("\n"))
+        }
+        Mode::AgentExport => {
+            let count = model.marked_agents.len().max(1);
+            let mut lines = vec![
+                format!("Export {count} agent(s) to file (.kdl, .json or .toml)"),
+                render_input_line("> ", &model.agent_transfer.path, cols),
+            ];
+            if model.agent_transfer.overwrite.is_some() {
+                lines.push("The file exists: Enter again to overwrite it".to_string());
+            }
+            Some(lines.join("\n"))
+        }
     }
 }
 
 fn render_agent_form_overlay(model: &Model, title: &str, cols: usize) -> String {
     let mut lines = Vec::new();
     lines.push(title.to_string());
-    let mk = |label: &str, val: &str, field: AgentFormField, current: AgentFormField| {
-        let marker = if field == current { ">" } else { " " };
-        format!(
-            "{marker} {label}: {}",
-            truncate(val, cols.saturating_sub(label.len() + 4))
-        )
-    };
-    lines.push(mk(
-        "Name",
-        &model.agent_form.name,
-        AgentFormField::Name,
-        model.agent_form.field,
-    ));
-    lines.push(mk(
-        "Command",
-        &model.agent_form.command,
-        AgentFormField::Command,
-        model.agent_form.field,
-    ));
-    lines.push(mk(
-        "Args",
-        &model.agent_form.args,
-        AgentFormField::Args,
-        model.agent_form.field,
-    ));
-    lines.push(mk(
-        "Note",
-        &model.agent_form.note,
-        AgentFormField::Note,
-        model.agent_form.field,
-    ));
+    let form = &model.agent_form;
+    let issues = form.issues(&model.agents);
+    let fields = [
+        ("Name", &form.name, AgentFormField::Name),
+        ("Command", &form.command, AgentFormField::Command),
+        ("Args", &form.args, AgentFormField::Args),
+        ("Note", &form.note, AgentFormField::Note),
+    ];
+    for (label, input, field) in fields {
+        // The focused field scrolls to its cursor; the others are cut off.
+        if field == form.field {
+            lines.push(render_input_line(&format!("> {label}: "), input, cols));
+        } else {
+            lines.push(format!(
+                "  {label}: {}",
+                truncate(input, cols.saturating_sub(label.len() + 4))
+            ));
+        }
+        if let Some(issue) = issues.get(field) {
+            let text = Text::new(truncate(&format!("    ! {issue}"), cols)).color_all(COLOR_RED);
+            lines.push(serialize_text(&text));
+        }
+    }
+    lines.extend(
+        command_preview_lines(model)
+            .iter()
+            .map(|line| truncate(line, cols)),
+    );
     lines.join("\n")
 }
 
-fn render_status(model: &Model, cols: usize) -> String {
-    let hints = match model.mode {
-        Mode::View => "j/k move • Enter focus • d kill • n new • c config • Esc close",
-        Mode::AgentConfig => "j/k move • a add • e edit • d delete • Esc back",
-        Mode::NewPaneWorkspace => "Tab accept • Enter continue • Esc cancel",
-        Mode::NewPaneAgentSelect => "j/k move • Enter select • Esc cancel",
-        Mode::AgentFormCreate | Mode::AgentFormEdit => {
-            "Tab/Shift-Tab move • Enter save • Esc cancel"
-        }
-        Mode::DeleteConfirm => "Enter/y confirm • Esc/n cancel",
+/// The command the form would save, as written and with sample placeholder
+/// values, and whether its executable was found.
+fn command_preview_lines(model: &Model) -> Vec<String> {
+    let Some(preview) = model.agent_form.preview(&TemplateContext::sample()) else {
+        return Vec::new();
     };
-    let msg = if !model.error_message.is_empty() {
+    let mut lines = vec![String::new(), format!("Runs: {}", preview.argv)];
+    // Only worth showing when the command has placeholders.
+    if preview.expanded != preview.argv {
+        lines.push(format!("For example: {}", preview.expanded));
+    }
+    let command = model.agent_form.command.trim();
+    let found = match model.command_availability.get(command) {
+        Some(Availability::Available) => "found",
+        Some(Availability::Missing) => "not found",
+        Some(Availability::Unknown) => "checking...",
+        None => "not checked (leave the field to check)",
+    };
+    lines.push(format!("Executable {command}: {found}"));
+    lines
+}
+
+/// `label` followed by the part of `input` around its cursor, with the
+/// cursor cell highlighted.
+fn render_input_line(label: &str, input: &TextInput, cols: usize) -> String {
+    let label_width = label.chars().count();
+    let (shown, cursor) = input.view(cols.saturating_sub(label_width));
+    let text = Text::new(format!("{label}{shown}"))
+        .color_indices(COLOR_CURSOR, vec![label_width + cursor]);
+    serialize_text(&text)
+}
+
+/// Status line hints for the current screen, built from the keymap.
+fn key_hints(model: &Model) -> String {
+    let browsing_recent = model.pane_wizard.workspace.trim().is_empty();
+    model
+        .config
+        .keymap
+        .hints(model.mode, |action| match action {
+            // The workspace step offers different actions for recent workspaces.
+            Action::ForgetWorkspace => browsing_recent,
+            Action::AcceptSuggestion | Action::ToggleHiddenDirs | Action::RescanProjects => {
+                !browsing_recent
+            }
+            _ => true,
+        })
+}
+
+fn render_status(model: &Model, cols: usize) -> String {
+    let hints = key_hints(model);
+    // The workspace step shows its errors under the input.
+    let inline_error = model.mode == Mode::NewPaneWorkspace;
+    let msg = if !model.error_message.is_empty() && !inline_error {
         format!("ERROR: {}", model.error_message)
+    } else if !model.notice.is_empty() {
+        model.notice.clone()
     } else {
-        hints.to_string()
+        hints
     };
     truncate(&msg, cols)
 }
@@ -351,6 +772,66 @@ mod tests {
         assert_eq!(next_field(AgentFormField::Note), AgentFormField::Name);
     }
 
+    #[test]
+    fn test_format_suggestion() {
+        let suggestion = WorkspaceSuggestion {
+            path: format!("{}/src/zellij", WASI_HOST_MOUNT),
+            matched: vec![10, 11, 12],
+            git: Some(true),
+        };
+        let (line, matched) = format_suggestion(&suggestion, true, 2, 80);
+        assert_eq!(line, "> src/zellij [git] [2 running]");
+        assert_eq!(matched, vec![6, 7, 8]);
+
+        let (line, matched) = format_suggestion(&suggestion, false, 0, 14);
+        assert_eq!(line, "  src/z… [git]");
+        assert_eq!(matched, vec![6]);
+    }
+
+    #[test]
+    fn test_command_preview_lines() {
+        let mut model = Model::default();
+        assert!(command_preview_lines(&model).is_empty());
+
+        model.agent_form.command = "codex".into();
+        model.agent_form.args = r#"--prompt "hello world" --cd {workspace}"#.into();
+        model
+            .command_availability
+            .insert("codex".to_string(), Availability::Missing);
+        assert_eq!(
+            command_preview_lines(&model),
+            vec![
+                "",
+                "Runs: codex --prompt 'hello world' --cd {workspace}",
+                "For example: codex --prompt 'hello world' --cd /home/you/project",
+                "Executable codex: not found",
+            ]
+        );
+
+        model.agent_form.args = "--fast".into();
+        assert_eq!(
+            command_preview_lines(&model)[1..3],
+            ["Runs: codex --fast", "Executable codex: not found"]
+        );
+    }
+
+    #[test]
+    fn test_render_help_scrolls_within_entries() {
+        let mut model = Model {
+            mode: Mode::Help,
+            rows: 6,
+            ..Default::default()
+        };
+        let title = |model: &Model| render_help(model, 80).lines().next().unwrap().to_string();
+        assert_eq!(title(&model), "Help: Agent panes (1-2 of 13)");
+
+        model.help.scroll = 100;
+        assert_eq!(title(&model), "Help: Agent panes (12-13 of 13)");
+
+        model.rows = 40;
+        assert_eq!(title(&model), "Help: Agent panes");
+    }
+
     #[test]
     fn test_prev_field() {
         assert_eq!(prev_field(AgentFormField::Name), AgentFormField::Note);
@@ -1,9 +1,10 @@
 //! Utility functions for path handling and string manipulation.
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::agent::Agent;
+use crate::agent::{Agent, AgentVariant};
 use crate::error::{MaestroError, MaestroResult};
 use crate::WASI_HOST_MOUNT;
 
@@ -14,88 +15,184 @@ pub struct DirEntry {
     pub path: PathBuf,
 }
 
-/// Read directory entries, filtering to directories only.
-pub fn read_directory(path: &Path) -> MaestroResult<Vec<DirEntry>> {
-    let entries = fs::read_dir(path).map_err(|e| MaestroError::FileRead {
-        path: path.to_path_buf(),
-        message: e.to_string(),
-    })?;
-    let mut dirs = Vec::new();
-
-    for entry in entries.flatten() {
-        if let Ok(metadata) = entry.metadata() {
-            if metadata.is_dir() {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let path = entry.path();
-                dirs.push(DirEntry { name, path });
-            }
+/// A workspace offered by the new-pane wizard.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceSuggestion {
+    pub path: String,
+    /// Char indices into `path` matched by the typed filter.
+    pub matched: Vec<usize>,
+    /// The directory is a git repository; `None` until checked, which
+    /// [`Self::resolve_git`] does only for the suggestions on screen.
+    pub git: Option<bool>,
+}
+
+impl WorkspaceSuggestion {
+    /// Check for a `.git` entry if that hasn't been done yet.
+    pub fn resolve_git(&mut self) {
+        if self.git.is_none() {
+            self.git = Some(is_git_repo(&self.path));
         }
     }
+}
 
-    dirs.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(dirs)
+/// Directory entries scored per listing; larger directories are cut off so
+/// a keypress never fuzzy-matches an unbounded list.
+const MAX_SCORED_ENTRIES: usize = 2_000;
+
+/// Seconds a cached directory listing stays valid.
+const DIR_CACHE_TTL_SECS: u64 = 30;
+
+/// Directory listings keyed by base path, so suggestions for a directory are
+/// loaded once rather than on every keypress and render. Listings are read
+/// in the background: a miss queues the directory (see [`DirCache::take_requests`])
+/// and suggestions fill in when [`DirCache::fill`] delivers it.
+#[derive(Debug, Clone, Default)]
+pub struct DirCache {
+    listings: BTreeMap<PathBuf, (u64, Vec<DirEntry>)>,
+    /// Directories being loaded, so a slow listing is requested only once.
+    loading: BTreeSet<PathBuf>,
+    /// Loads not yet handed to the host.
+    requests: Vec<PathBuf>,
 }
 
-/// Get autocomplete suggestions for a partial path.
-pub fn get_path_suggestions(partial_path: &str) -> Vec<String> {
-    use fuzzy_matcher::skim::SkimMatcherV2;
-    use fuzzy_matcher::FuzzyMatcher;
+impl DirCache {
+    /// Entries of `path`, or `None` until its first listing arrives. A
+    /// missing or stale listing is queued for loading; a stale one is still
+    /// returned meanwhile.
+    pub fn entries(&mut self, path: &Path, now: u64) -> Option<&[DirEntry]> {
+        let stale = self
+            .listings
+            .get(path)
+            .is_none_or(|(loaded, _)| now.saturating_sub(*loaded) >= DIR_CACHE_TTL_SECS);
+        if stale && self.loading.insert(path.to_path_buf()) {
+            self.requests.push(path.to_path_buf());
+        }
+        self.listings
+            .get(path)
+            .map(|(_, entries)| entries.as_slice())
+    }
+
+    /// Directories to list, handing each out once.
+    pub fn take_requests(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.requests)
+    }
+
+    /// Store a loaded listing. Unreadable directories are stored as empty.
+    pub fn fill(&mut self, path: &Path, mut entries: Vec<DirEntry>, now: u64) {
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        self.loading.remove(path);
+        self.listings.insert(path.to_path_buf(), (now, entries));
+    }
 
+    /// Forget the listing of `path`, e.g. after creating a directory in it.
+    pub fn invalidate(&mut self, path: &Path) {
+        self.listings.remove(path);
+    }
+
+    pub fn clear(&mut self) {
+        self.listings.clear();
+        self.loading.clear();
+    }
+
+    /// Autocomplete suggestions for a partial path, or `None` while its
+    /// directory is loading. Hidden directories are left out unless
+    /// `show_hidden` is set or the filter starts with `.`.
+    pub fn path_suggestions(
+        &mut self,
+        partial_path: &str,
+        now: u64,
+        show_hidden: bool,
+    ) -> Option<Vec<WorkspaceSuggestion>> {
+        let Some((base_path, filter)) = split_path_input(partial_path) else {
+            return Some(Vec::new());
+        };
+        let show_hidden = show_hidden || filter.starts_with('.');
+        let entries = self.entries(&base_path, now)?;
+        Some(score_path_entries(entries, &filter, show_hidden))
+    }
+}
+
+/// Parse the NUL-separated paths `find -print0` lists for a directory into
+/// entries of `base`, the same directory seen through the `/host` mount.
+pub fn parse_dir_listing(base: &Path, output: &[u8]) -> Vec<DirEntry> {
+    output
+        .split(|b| *b == 0)
+        .filter_map(|raw| {
+            let line = String::from_utf8_lossy(raw);
+            let name = Path::new(line.as_ref())
+                .file_name()?
+                .to_string_lossy()
+                .into_owned();
+            Some(DirEntry {
+                path: base.join(&name),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// The argument naming `path` (under `/host`) for a command run in the
+/// plugin `cwd`.
+pub fn host_relative_arg(path: &Path) -> String {
+    match path.strip_prefix(wasi_root()) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => format!("./{}", rel.to_string_lossy()),
+        Err(_) => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// Split a partial path into the directory to list (under the `/host`
+/// mount) and the fuzzy filter for its entries.
+fn split_path_input(partial_path: &str) -> Option<(PathBuf, String)> {
     let trimmed = partial_path.trim();
     if trimmed.is_empty() {
-        return Vec::new();
+        return None;
     }
 
     let root = wasi_root();
     let host_prefix = format!("{}/", WASI_HOST_MOUNT);
+    let relative = if trimmed == WASI_HOST_MOUNT {
+        ""
+    } else {
+        trimmed.strip_prefix(&host_prefix).unwrap_or(trimmed)
+    };
 
-    let (base_path, filter_segment) = if trimmed == WASI_HOST_MOUNT || trimmed == host_prefix {
-        (root.clone(), String::new())
-    } else if trimmed.starts_with(&host_prefix) {
-        let relative = trimmed.strip_prefix(&host_prefix).unwrap_or("");
-        if relative.is_empty() {
-            (root.clone(), String::new())
-        } else if relative.ends_with('/') {
-            (root.join(relative.trim_end_matches('/')), String::new())
-        } else {
-            let parts: Vec<&str> = relative.split('/').collect();
-            if parts.len() == 1 {
-                (root.clone(), parts[0].to_string())
-            } else {
-                let base = parts[..parts.len() - 1].join("/");
-                let filter = parts.last().unwrap_or(&"").to_string();
-                (root.join(base), filter)
-            }
-        }
-    } else if trimmed.ends_with('/') {
-        (root.join(trimmed.trim_end_matches('/')), String::new())
+    if relative.is_empty() {
+        Some((root, String::new()))
+    } else if relative.ends_with('/') {
+        Some((root.join(relative.trim_end_matches('/')), String::new()))
     } else {
-        let parts: Vec<&str> = trimmed.split('/').collect();
-        if parts.len() == 1 {
-            (root.clone(), parts[0].to_string())
-        } else {
-            let base = parts[..parts.len() - 1].join("/");
-            let filter = parts.last().unwrap_or(&"").to_string();
-            (root.join(base), filter)
+        match relative.rsplit_once('/') {
+            Some((base, filter)) => Some((root.join(base), filter.to_string())),
+            None => Some((root, relative.to_string())),
         }
-    };
+    }
+}
 
-    let entries = match read_directory(&base_path) {
-        Ok(e) => e,
-        Err(_) => return Vec::new(),
-    };
+/// Score directory entries against `filter`, best match first, returning
+/// `/host/...` paths. Only the first [`MAX_SCORED_ENTRIES`] are considered.
+fn score_path_entries(
+    entries: &[DirEntry],
+    filter: &str,
+    show_hidden: bool,
+) -> Vec<WorkspaceSuggestion> {
+    use fuzzy_matcher::skim::SkimMatcherV2;
+    use fuzzy_matcher::FuzzyMatcher;
 
+    let root = wasi_root();
     let matcher = SkimMatcherV2::default();
 
-    let mut scored: Vec<(i64, String)> = entries
+    let mut scored: Vec<(i64, WorkspaceSuggestion)> = entries
         .iter()
+        .filter(|entry| show_hidden || !entry.name.starts_with('.'))
+        .take(MAX_SCORED_ENTRIES)
         .filter_map(|entry| {
             let name = &entry.name;
             // Neutral score when no filter is provided so we still surface all entries.
-            let score = if filter_segment.is_empty() {
-                0
+            let (score, indices) = if filter.is_empty() {
+                (0, Vec::new())
             } else {
-                matcher.fuzzy_match(name, &filter_segment)?
+                matcher.fuzzy_indices(name, filter)?
             };
 
             let relative = if entry.path.starts_with(&root) {
@@ -108,14 +205,33 @@ pub fn get_path_suggestions(partial_path: &str) -> Vec<String> {
                 WASI_HOST_MOUNT,
                 relative.to_string_lossy().trim_start_matches('/')
             );
-            Some((score, display))
+            // The name is the last component, so shift its indices to the end.
+            let offset = display.chars().count() - name.chars().count();
+            Some((
+                score,
+                WorkspaceSuggestion {
+                    path: display,
+                    matched: indices.into_iter().map(|i| i + offset).collect(),
+                    git: None,
+                },
+            ))
         })
         .collect();
 
     // Sort by best match first, then by path name for stable ordering.
-    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
 
-    scored.into_iter().map(|(_, path)| path).collect()
+    scored
+        .into_iter()
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+/// Whether a workspace the plugin can see is a git repository.
+pub fn is_git_repo(workspace: &str) -> bool {
+    let trimmed = workspace.trim();
+    (trimmed == WASI_HOST_MOUNT || trimmed.starts_with(&format!("{}/", WASI_HOST_MOUNT)))
+        && host_fs_path(trimmed).join(".git").exists()
 }
 
 /// Truncate a string to a maximum length, adding ellipsis if needed.
@@ -139,8 +255,26 @@ pub fn truncate(s: &str, max: usize) -> String {
 
 /// Build command as a list of strings (command followed by args).
 pub fn build_command(agent: &Agent) -> Vec<String> {
-    let mut parts = vec![agent.command.clone()];
-    parts.extend(agent.args.clone());
+    build_variant_command(agent, None)
+}
+
+/// Build the command for an agent with an optional variant applied.
+/// Variant environment variables are passed through an `env` prefix.
+pub fn build_variant_command(agent: &Agent, variant: Option<&AgentVariant>) -> Vec<String> {
+    let mut parts = Vec::new();
+    if let Some(v) = variant.filter(|v| !v.env.is_empty()) {
+        parts.push("env".to_string());
+        parts.extend(v.env.iter().map(|(key, value)| format!("{key}={value}")));
+    }
+    parts.push(agent.command.clone());
+    match variant {
+        Some(v) if v.replace_args => parts.extend(v.args.clone()),
+        Some(v) => {
+            parts.extend(agent.args.clone());
+            parts.extend(v.args.clone());
+        }
+        None => parts.extend(agent.args.clone()),
+    }
     parts
 }
 
@@ -188,46 +322,328 @@ pub fn resolve_workspace_path(path: &str) -> Option<PathBuf> {
     }
 }
 
-/// Filter agents by fuzzy matching against the filter string.
-/// Returns indices of matching agents sorted by score (best first).
-pub fn filter_agents_fuzzy(agents: &[Agent], filter: &str) -> Vec<usize> {
-    use fuzzy_matcher::skim::SkimMatcherV2;
-    use fuzzy_matcher::FuzzyMatcher;
+/// Directories a typed workspace is resolved against. All are host paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathContext {
+    /// Host directory mounted at `/host` (the plugin `cwd`).
+    pub host_cwd: Option<PathBuf>,
+    /// Host home directory, for `~`.
+    pub home: Option<PathBuf>,
+    /// Directory relative paths start from (the focused pane's cwd, when known).
+    pub base: Option<PathBuf>,
+}
 
-    let filter = filter.trim();
-    if filter.is_empty() {
-        return (0..agents.len()).collect();
+/// Expand a typed workspace into the form the rest of the wizard uses:
+/// `/host/...` when the plugin can see it, otherwise an absolute host path.
+/// `~` expands to the configured home directory (left as typed when unset)
+/// and relative paths start from `ctx.base`, falling back to the plugin `cwd`.
+/// A trailing `/` is kept so suggestions list the directory's children.
+pub fn expand_workspace_input(input: &str, ctx: &PathContext) -> String {
+    let trimmed = input.trim();
+    if trimmed.is_empty()
+        || trimmed == WASI_HOST_MOUNT
+        || trimmed.starts_with(&format!("{}/", WASI_HOST_MOUNT))
+    {
+        return trimmed.to_string();
     }
+    let Some(host_cwd) = ctx.host_cwd.as_deref() else {
+        return trimmed.to_string();
+    };
 
-    let matcher = SkimMatcherV2::default();
-    let mut scored: Vec<(usize, i64)> = agents
-        .iter()
-        .enumerate()
-        .filter_map(|(idx, agent)| {
-            matcher
-                .fuzzy_match(&agent.name, filter)
-                .map(|score| (idx, score))
-        })
-        .collect();
+    let host_path = if let Some(rest) = home_relative(trimmed) {
+        match &ctx.home {
+            Some(home) => home.join(rest.trim_start_matches('/')),
+            None => return trimmed.to_string(),
+        }
+    } else if trimmed.starts_with('/') {
+        PathBuf::from(trimmed)
+    } else {
+        ctx.base.as_deref().unwrap_or(host_cwd).join(trimmed)
+    };
 
-    scored.sort_by(|a, b| b.1.cmp(&a.1));
+    let normalized = normalize_path(&host_path);
+    let mut expanded = match normalized.strip_prefix(host_cwd) {
+        Ok(rel) if rel.as_os_str().is_empty() => WASI_HOST_MOUNT.to_string(),
+        Ok(rel) => format!("{}/{}", WASI_HOST_MOUNT, rel.to_string_lossy()),
+        Err(_) => normalized.to_string_lossy().into_owned(),
+    };
+    if trimmed.ends_with('/') && !expanded.ends_with('/') {
+        expanded.push('/');
+    }
+    expanded
+}
 
-    scored.into_iter().map(|(idx, _)| idx).collect()
+/// The part after `~` when `path` is `~` or `~/...`.
+fn home_relative(path: &str) -> Option<&str> {
+    path.strip_prefix('~')
+        .filter(|r| r.is_empty() || r.starts_with('/'))
+}
+
+/// Check that an expanded workspace is inside the `/host` mount, where the
+/// plugin can list and inspect it.
+pub fn check_visible(expanded: &str, ctx: &PathContext) -> MaestroResult<()> {
+    let trimmed = expanded.trim();
+    if home_relative(trimmed).is_some() {
+        return Err(MaestroError::HomeNotSet);
+    }
+    if is_visible(trimmed) {
+        return Ok(());
+    }
+    Err(MaestroError::OutsideSandbox {
+        path: trimmed.trim_end_matches('/').to_string(),
+        root: ctx
+            .host_cwd
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|| WASI_HOST_MOUNT.to_string()),
+    })
+}
+
+/// Check that a workspace names an existing directory, looking through the
+/// `/host` mount. Empty workspaces (the plugin `cwd`) and absolute paths
+/// outside the mount cannot be checked and pass.
+pub fn validate_workspace(workspace: &str) -> MaestroResult<()> {
+    let trimmed = workspace.trim();
+    if home_relative(trimmed).is_some() {
+        return Err(MaestroError::HomeNotSet);
+    }
+    if trimmed.is_empty() || !is_visible(trimmed) {
+        return Ok(());
+    }
+    check_workspace_dir(&host_fs_path(trimmed), display_workspace(trimmed))
+}
+
+fn check_workspace_dir(path: &Path, display: &str) -> MaestroResult<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        Ok(_) => Err(MaestroError::WorkspaceNotDirectory(display.to_string())),
+        Err(_) => Err(MaestroError::WorkspaceNotFound(display.to_string())),
+    }
+}
+
+/// Create a missing workspace directory through the `/host` mount.
+pub fn create_workspace(workspace: &str) -> MaestroResult<()> {
+    let path = host_fs_path(workspace);
+    fs::create_dir_all(&path).map_err(|e| MaestroError::DirectoryCreate {
+        path,
+        message: e.to_string(),
+    })
+}
+
+/// A workspace as shown to the user, without the `/host/` prefix.
+pub fn display_workspace(workspace: &str) -> &str {
+    workspace
+        .strip_prefix(&format!("{}/", WASI_HOST_MOUNT))
+        .unwrap_or(workspace)
+}
+
+/// Whether a path can be seen through the `/host` mount.
+fn is_visible(path: &str) -> bool {
+    !path.starts_with('/')
+        || path == WASI_HOST_MOUNT
+        || path.starts_with(&format!("{}/", WASI_HOST_MOUNT))
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Map a workspace to a path readable through the WASI host mount.
+/// Absolute paths are kept; relative paths resolve against the mount root.
+pub fn host_fs_path(input: &str) -> PathBuf {
+    let trimmed = input.trim();
+    if trimmed.starts_with('/') {
+        return PathBuf::from(trimmed);
+    }
+    wasi_root().join(trimmed)
+}
+
+/// Map a user-entered file path (import/export) to a path readable through
+/// the host mount. `~` and relative paths expand as in
+/// [`expand_workspace_input`]; absolute paths outside the mount are kept.
+pub fn host_file_path(input: &str, ctx: &PathContext) -> PathBuf {
+    host_fs_path(&expand_workspace_input(input, ctx))
 }
 
 /// Find an agent by matching the pane title to the agent's command.
 pub fn find_agent_by_command<'a>(agents: &'a [Agent], pane_title: &str) -> Option<&'a Agent> {
-    let title_base = pane_title.split(" - ").next().unwrap_or(pane_title).trim();
+    find_agent_variant_by_command(agents, pane_title).map(|(agent, _)| agent)
+}
 
-    agents.iter().find(|agent| {
-        if agent.command.trim().is_empty() {
-            return false;
-        }
-        let full_cmd = build_command(agent).join(" ");
-        full_cmd.eq_ignore_ascii_case(title_base)
+/// Find an agent, and the variant it was launched as, from a pane's command
+/// line. Uses [`match_agent_command`]; ambiguous matches find nothing.
+pub fn find_agent_variant_by_command<'a>(
+    agents: &'a [Agent],
+    pane_title: &str,
+) -> Option<(&'a Agent, Option<&'a AgentVariant>)> {
+    let AgentMatch::Unique(name) = match_agent_command(agents, pane_title) else {
+        return None;
+    };
+    agents.iter().find_map(|agent| {
+        std::iter::once(None)
+            .chain(agent.variants.iter().map(Some))
+            .find(|variant| agent.display_name(*variant) == name)
+            .map(|variant| (agent, variant))
     })
 }
 
+/// Result of matching a pane's command line against the configured agents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentMatch {
+    None,
+    /// Display name of the single best match.
+    Unique(String),
+    /// Display names of equally good matches.
+    Ambiguous(Vec<String>),
+}
+
+/// Match a pane's command line against every agent and variant.
+///
+/// An exact command wins, then commands whose arguments start with the
+/// agent's arguments, then ones containing them in any order (an agent
+/// without arguments matches its bare executable, so `/usr/bin/claude
+/// --resume` still finds `claude`). A pane missing any of an agent's
+/// arguments is not that agent's. Ties at the best score are reported as
+/// ambiguous.
+pub fn match_agent_command(agents: &[Agent], command_line: &str) -> AgentMatch {
+    let base = command_line
+        .split(" - ")
+        .next()
+        .unwrap_or(command_line)
+        .trim();
+    let tokens = shell_words::split(base)
+        .unwrap_or_else(|_| base.split_whitespace().map(str::to_string).collect());
+    if tokens.is_empty() {
+        return AgentMatch::None;
+    }
+
+    let mut best = 0;
+    let mut names: Vec<String> = Vec::new();
+    for agent in agents.iter().filter(|a| !a.command.trim().is_empty()) {
+        let candidates = std::iter::once(None).chain(agent.variants.iter().map(Some));
+        for variant in candidates {
+            let Some(score) = command_score(&build_variant_command(agent, variant), &tokens) else {
+                continue;
+            };
+            if score > best {
+                best = score;
+                names.clear();
+            }
+            if score == best {
+                names.push(agent.display_name(variant));
+            }
+        }
+    }
+
+    match names.len() {
+        0 => AgentMatch::None,
+        1 => AgentMatch::Unique(names.remove(0)),
+        _ => AgentMatch::Ambiguous(names),
+    }
+}
+
+fn command_score(candidate: &[String], pane: &[String]) -> Option<usize> {
+    let (candidate_exe, candidate_args) = split_executable(candidate)?;
+    let (pane_exe, pane_args) = split_executable(pane)?;
+    if !executable_name(candidate_exe).eq_ignore_ascii_case(executable_name(pane_exe)) {
+        return None;
+    }
+
+    let specificity = candidate_args.len();
+    if candidate == pane {
+        Some(3000 + specificity)
+    } else if pane_args.starts_with(candidate_args) {
+        Some(2000 + specificity)
+    } else if candidate_args.iter().all(|a| pane_args.contains(a)) {
+        Some(1000 + specificity)
+    } else {
+        None
+    }
+}
+
+/// Split a command into its executable and arguments, skipping an `env K=V` prefix.
+fn split_executable(command: &[String]) -> Option<(&str, &[String])> {
+    let mut rest = command;
+    if rest.first().map(String::as_str) == Some("env") {
+        rest = &rest[1..];
+        while rest.first().is_some_and(|t| t.contains('=')) {
+            rest = &rest[1..];
+        }
+    }
+    let (exe, args) = rest.split_first()?;
+    Some((exe.as_str(), args))
+}
+
+fn executable_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// An entry in the agent picker: a base agent or one of its variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentChoice {
+    pub agent: usize,
+    pub variant: Option<usize>,
+}
+
+impl AgentChoice {
+    /// Display name of this choice (`claude` or `claude › yolo`).
+    pub fn label(&self, agents: &[Agent]) -> String {
+        let agent = &agents[self.agent];
+        agent.display_name(self.variant.and_then(|v| agent.variants.get(v)))
+    }
+}
+
+/// List picker entries for visible agents and their variants, fuzzy-filtered
+/// by label and sorted by score (best first).
+pub fn filter_agent_choices(agents: &[Agent], filter: &str) -> Vec<AgentChoice> {
+    use fuzzy_matcher::skim::SkimMatcherV2;
+    use fuzzy_matcher::FuzzyMatcher;
+
+    let choices = agents
+        .iter()
+        .enumerate()
+        .filter(|(_, agent)| !agent.hidden)
+        .flat_map(|(idx, agent)| {
+            std::iter::once(AgentChoice {
+                agent: idx,
+                variant: None,
+            })
+            .chain((0..agent.variants.len()).map(move |v| AgentChoice {
+                agent: idx,
+                variant: Some(v),
+            }))
+        });
+
+    let filter = filter.trim();
+    if filter.is_empty() {
+        return choices.collect();
+    }
+
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(AgentChoice, i64)> = choices
+        .filter_map(|choice| {
+            matcher
+                .fuzzy_match(&choice.label(agents), filter)
+                .map(|score| (choice, score))
+        })
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(choice, _)| choice).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,6 +667,7 @@ mod tests {
             command: "echo".to_string(),
             args: vec!["hello".to_string(), "world".to_string()],
             note: None,
+            ..Default::default()
         };
 
         let cmd = build_command(&agent);
@@ -264,6 +681,7 @@ mod tests {
             command: "echo".to_string(),
             args: Vec::new(),
             note: None,
+            ..Default::default()
         };
 
         let cmd = build_command(&agent);
@@ -286,27 +704,113 @@ mod tests {
         assert_eq!(default_tab_name("/"), "workspace");
     }
 
+    #[test]
+    fn test_filter_agent_choices_skips_hidden() {
+        let mut agents = vec![
+            crate::test_helpers::create_test_agent("claude"),
+            crate::test_helpers::create_test_agent("codex"),
+            crate::test_helpers::create_test_agent("cursor"),
+        ];
+        agents[1].hidden = true;
+
+        let indices = |filter: &str| -> Vec<usize> {
+            filter_agent_choices(&agents, filter)
+                .iter()
+                .map(|c| c.agent)
+                .collect()
+        };
+        assert_eq!(indices(""), vec![0, 2]);
+        assert_eq!(indices("cod"), Vec::<usize>::new());
+        assert_eq!(indices("cur"), vec![2]);
+    }
+
+    #[test]
+    fn test_host_fs_path() {
+        assert_eq!(
+            host_fs_path("shared/agents.kdl"),
+            PathBuf::from(format!("{}/shared/agents.kdl", WASI_HOST_MOUNT))
+        );
+        assert_eq!(
+            host_fs_path(&format!("  {}/team/agents.toml ", WASI_HOST_MOUNT)),
+            PathBuf::from(format!("{}/team/agents.toml", WASI_HOST_MOUNT))
+        );
+    }
+
+    #[test]
+    fn test_host_file_path() {
+        let ctx = PathContext {
+            host_cwd: Some(PathBuf::from("/home/me")),
+            home: Some(PathBuf::from("/home/me")),
+            base: Some(PathBuf::from("/home/me/src/app")),
+        };
+        let mounted = |rel: &str| PathBuf::from(format!("{}/{}", WASI_HOST_MOUNT, rel));
+        // `~` is the home directory, as for workspaces, not the mount root.
+        assert_eq!(
+            host_file_path("~/agents.json", &ctx),
+            mounted("agents.json")
+        );
+        assert_eq!(
+            host_file_path("shared/agents.kdl", &ctx),
+            mounted("src/app/shared/agents.kdl")
+        );
+        assert_eq!(
+            host_file_path("/home/me/team/agents.toml", &ctx),
+            mounted("team/agents.toml")
+        );
+        assert_eq!(
+            host_file_path("/tmp/agents.kdl", &ctx),
+            PathBuf::from("/tmp/agents.kdl")
+        );
+    }
+
+    fn variant_agent() -> Agent {
+        Agent {
+            name: "claude".to_string(),
+            command: "claude".to_string(),
+            args: vec!["--verbose".to_string()],
+            variants: vec![
+                AgentVariant {
+                    name: "yolo".to_string(),
+                    args: vec!["--dangerously-skip-permissions".to_string()],
+                    ..Default::default()
+                },
+                AgentVariant {
+                    name: "opus".to_string(),
+                    args: vec!["--model".to_string(), "opus".to_string()],
+                    replace_args: true,
+                    env: [("MAX_THINKING".to_string(), "1".to_string())].into(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_variant_command() {
+        let agent = variant_agent();
+        assert_eq!(
+            build_variant_command(&agent, agent.variants.first()),
+            vec!["claude", "--verbose", "--dangerously-skip-permissions"]
+        );
+        assert_eq!(
+            build_variant_command(&agent, agent.variants.get(1)),
+            vec!["env", "MAX_THINKING=1", "claude", "--model", "opus"]
+        );
+    }
+
     #[test]
     fn test_find_agent_by_command() {
+        let agent = |name: &str, command: &str, args: &[&str]| Agent {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            note: None,
+            ..Default::default()
+        };
         let agents = vec![
-            Agent {
-                name: "cursor".to_string(),
-                command: "cursor-agent".to_string(),
-                args: Vec::new(),
-                note: None,
-            },
-            Agent {
-                name: "claude".to_string(),
-                command: "claude".to_string(),
-                args: Vec::new(),
-                note: None,
-            },
-            Agent {
-                name: "custom".to_string(),
-                command: "my-cmd".to_string(),
-                args: vec!["arg1".to_string()],
-                note: None,
-            },
+            agent("cursor", "cursor-agent", &[]),
+            agent("claude", "claude", &[]),
+            agent("custom", "my-cmd", &["arg1"]),
         ];
 
         assert_eq!(
@@ -330,21 +834,15 @@ mod tests {
 
         assert_eq!(find_agent_by_command(&agents, "unknown"), None);
         assert_eq!(find_agent_by_command(&agents, "my-cmd"), None);
-        assert_eq!(find_agent_by_command(&agents, "my-cmd arg1 arg2"), None);
+        // Arguments added after the agent's own still identify it.
+        assert_eq!(
+            find_agent_by_command(&agents, "my-cmd arg1 arg2"),
+            Some(&agents[2])
+        );
 
         let agents_with_overlap = vec![
-            Agent {
-                name: "codex".to_string(),
-                command: "codex".to_string(),
-                args: Vec::new(),
-                note: None,
-            },
-            Agent {
-                name: "codex-reviewer".to_string(),
-                command: "codex".to_string(),
-                args: vec!["/review".to_string()],
-                note: None,
-            },
+            agent("codex", "codex", &[]),
+            agent("codex-reviewer", "codex", &["/review"]),
         ];
         assert_eq!(
             find_agent_by_command(&agents_with_overlap, "codex /review"),
@@ -356,6 +854,325 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_agent_variant_by_command() {
+        let agents = vec![variant_agent()];
+        let found = |line: &str| {
+            find_agent_variant_by_command(&agents, line).map(|(a, v)| a.display_name(v))
+        };
+        assert_eq!(
+            found("claude --verbose --dangerously-skip-permissions"),
+            Some("claude › yolo".to_string())
+        );
+        assert_eq!(
+            found("env MAX_THINKING=1 claude --model opus"),
+            Some("claude › opus".to_string())
+        );
+        assert_eq!(found("claude --verbose"), Some("claude".to_string()));
+        assert_eq!(found("claude --model sonnet"), None);
+    }
+
+    #[test]
+    fn test_match_agent_command() {
+        let mut reviewer = crate::test_helpers::create_test_agent("reviewer");
+        reviewer.command = "claude".to_string();
+        reviewer.args = vec!["--review".to_string()];
+        let agents = vec![variant_agent(), reviewer];
+        let unique = |name: &str| AgentMatch::Unique(name.to_string());
+
+        assert_eq!(
+            match_agent_command(&agents, "claude --verbose --dangerously-skip-permissions"),
+            unique("claude › yolo")
+        );
+        assert_eq!(
+            match_agent_command(&agents, "env MAX_THINKING=1 claude --model opus"),
+            unique("claude › opus")
+        );
+        // Arguments added after spawn still resolve to the base agent.
+        assert_eq!(
+            match_agent_command(&agents, "/usr/local/bin/claude --verbose --resume"),
+            unique("claude")
+        );
+        assert_eq!(
+            match_agent_command(&agents, "claude 'review this' --review"),
+            unique("reviewer")
+        );
+        // Sharing `--model` is not enough for the opus variant; the pane
+        // falls back to the base agent whose arguments it does contain.
+        assert_eq!(
+            match_agent_command(&agents, "claude --verbose --model sonnet"),
+            unique("claude")
+        );
+        assert_eq!(
+            match_agent_command(&agents, "claude --help"),
+            AgentMatch::None
+        );
+        assert_eq!(match_agent_command(&agents, "codex"), AgentMatch::None);
+        assert_eq!(match_agent_command(&agents, ""), AgentMatch::None);
+    }
+
+    #[test]
+    fn test_match_agent_command_reports_ambiguity() {
+        let mut first = crate::test_helpers::create_test_agent("first");
+        first.args.clear();
+        let mut second = crate::test_helpers::create_test_agent("second");
+        second.args.clear();
+        let agents = vec![first, second];
+
+        assert_eq!(
+            match_agent_command(&agents, "echo"),
+            AgentMatch::Ambiguous(vec!["first".to_string(), "second".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_filter_agent_choices_expands_variants() {
+        let mut hidden = crate::test_helpers::create_test_agent("codex");
+        hidden.hidden = true;
+        let agents = vec![variant_agent(), hidden];
+
+        let labels: Vec<String> = filter_agent_choices(&agents, "")
+            .iter()
+            .map(|c| c.label(&agents))
+            .collect();
+        assert_eq!(labels, vec!["claude", "claude › yolo", "claude › opus"]);
+
+        let filtered = filter_agent_choices(&agents, "yolo");
+        assert_eq!(
+            filtered,
+            vec![AgentChoice {
+                agent: 0,
+                variant: Some(0)
+            }]
+        );
+    }
+
+    fn path_ctx() -> PathContext {
+        PathContext {
+            host_cwd: Some(PathBuf::from("/home/me/src")),
+            home: Some(PathBuf::from("/home/me")),
+            base: Some(PathBuf::from("/home/me/src/app")),
+        }
+    }
+
+    #[test]
+    fn test_expand_workspace_input() {
+        let ctx = path_ctx();
+        let mounted = |rel: &str| format!("{}/{}", WASI_HOST_MOUNT, rel);
+
+        assert_eq!(expand_workspace_input("~/src/api", &ctx), mounted("api"));
+        assert_eq!(expand_workspace_input("~/src", &ctx), WASI_HOST_MOUNT);
+        assert_eq!(
+            expand_workspace_input("/home/me/src/web/", &ctx),
+            mounted("web/")
+        );
+        assert_eq!(expand_workspace_input("../lib", &ctx), mounted("lib"));
+        assert_eq!(expand_workspace_input("./docs", &ctx), mounted("app/docs"));
+        assert_eq!(expand_workspace_input("/opt/work", &ctx), "/opt/work");
+        assert_eq!(expand_workspace_input("~/notes", &ctx), "/home/me/notes");
+        assert_eq!(expand_workspace_input(&mounted("x"), &ctx), mounted("x"));
+        assert_eq!(expand_workspace_input("~user", &ctx), mounted("app/~user"));
+    }
+
+    #[test]
+    fn test_expand_workspace_input_fallbacks() {
+        // Without a base relative paths use the plugin cwd; without a home
+        // `~` is left as typed and reported as an error.
+        let ctx = PathContext {
+            host_cwd: Some(PathBuf::from("/home/me")),
+            ..Default::default()
+        };
+        let mounted = |rel: &str| format!("{}/{}", WASI_HOST_MOUNT, rel);
+        assert_eq!(expand_workspace_input("~/src", &ctx), "~/src");
+        assert_eq!(expand_workspace_input("src/app", &ctx), mounted("src/app"));
+        let err = check_visible("~/src", &ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot expand ~: set `home` in the plugin configuration"
+        );
+        assert!(validate_workspace("~").is_err());
+
+        // Without the plugin cwd nothing can be mapped.
+        let ctx = PathContext::default();
+        assert_eq!(expand_workspace_input("src/app", &ctx), "src/app");
+    }
+
+    #[test]
+    fn test_check_visible() {
+        let ctx = path_ctx();
+        assert!(check_visible(&format!("{}/api", WASI_HOST_MOUNT), &ctx).is_ok());
+        assert!(check_visible("relative", &ctx).is_ok());
+        let err = check_visible("/opt/work/", &ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "/opt/work is outside what Maestro can see (/home/me/src)"
+        );
+    }
+
+    #[test]
+    fn test_check_workspace_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "").unwrap();
+
+        assert!(check_workspace_dir(dir.path(), "dir").is_ok());
+        assert!(matches!(
+            check_workspace_dir(&file, "notes.txt"),
+            Err(MaestroError::WorkspaceNotDirectory(_))
+        ));
+        assert!(matches!(
+            check_workspace_dir(&dir.path().join("missing"), "missing"),
+            Err(MaestroError::WorkspaceNotFound(p)) if p == "missing"
+        ));
+    }
+
+    #[test]
+    fn test_validate_workspace_skips_unchecked_paths() {
+        assert!(validate_workspace("").is_ok());
+        assert!(validate_workspace("/opt/outside/the/mount").is_ok());
+        assert!(matches!(
+            validate_workspace(&format!("{}/no/such/workspace", WASI_HOST_MOUNT)),
+            Err(MaestroError::WorkspaceNotFound(p)) if p == "no/such/workspace"
+        ));
+    }
+
+    #[test]
+    fn test_split_path_input() {
+        let root = PathBuf::from(WASI_HOST_MOUNT);
+        assert_eq!(split_path_input("  "), None);
+        assert_eq!(
+            split_path_input(WASI_HOST_MOUNT),
+            Some((root.clone(), String::new()))
+        );
+        assert_eq!(
+            split_path_input(&format!("{}/src/", WASI_HOST_MOUNT)),
+            Some((root.join("src"), String::new()))
+        );
+        assert_eq!(
+            split_path_input(&format!("{}/src/ma", WASI_HOST_MOUNT)),
+            Some((root.join("src"), "ma".to_string()))
+        );
+        assert_eq!(split_path_input("ma"), Some((root, "ma".to_string())));
+    }
+
+    #[test]
+    fn test_score_path_entries() {
+        let entries: Vec<DirEntry> = ["maestro", "zellij", "mars", ".config"]
+            .iter()
+            .map(|name| DirEntry {
+                name: name.to_string(),
+                path: PathBuf::from(WASI_HOST_MOUNT).join("src").join(name),
+            })
+            .collect();
+        let mounted = |rel: &str| format!("{}/{}", WASI_HOST_MOUNT, rel);
+        let paths = |suggestions: Vec<WorkspaceSuggestion>| -> Vec<String> {
+            suggestions.into_iter().map(|s| s.path).collect()
+        };
+
+        assert_eq!(
+            paths(score_path_entries(&entries, "", false)),
+            vec![
+                mounted("src/maestro"),
+                mounted("src/mars"),
+                mounted("src/zellij")
+            ]
+        );
+        assert_eq!(
+            paths(score_path_entries(&entries, "", true))[0],
+            mounted("src/.config")
+        );
+
+        let zellij = score_path_entries(&entries, "zel", false);
+        assert_eq!(zellij.len(), 1);
+        assert_eq!(zellij[0].path, mounted("src/zellij"));
+        assert_eq!(zellij[0].git, None);
+        // "/host/src/" is 10 chars; "zel" are the first three of the name.
+        assert_eq!(zellij[0].matched, vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn test_path_suggestions_hidden_toggle() {
+        let base = PathBuf::from(WASI_HOST_MOUNT).join("src");
+        let input = format!("{}/src/", WASI_HOST_MOUNT);
+        let mut cache = DirCache::default();
+        assert_eq!(cache.path_suggestions(&input, 0, false), None);
+        cache.fill(
+            &base,
+            parse_dir_listing(&base, b"./src/.hidden\0./src/shown\0"),
+            0,
+        );
+
+        let count = |cache: &mut DirCache, input: &str, hidden: bool| {
+            cache.path_suggestions(input, 0, hidden).unwrap().len()
+        };
+        assert_eq!(count(&mut cache, &input, false), 1);
+        assert_eq!(count(&mut cache, &input, true), 2);
+        assert_eq!(count(&mut cache, &format!("{input}.hid"), false), 1);
+    }
+
+    #[test]
+    fn test_dir_cache_loads_once_until_stale() {
+        let dir = PathBuf::from("/host/src");
+        let entry = |name: &str| DirEntry {
+            name: name.to_string(),
+            path: dir.join(name),
+        };
+        let mut cache = DirCache::default();
+
+        assert_eq!(cache.entries(&dir, 100), None);
+        assert_eq!(cache.entries(&dir, 100), None);
+        // Asked for once while the load is outstanding.
+        assert_eq!(cache.take_requests(), vec![dir.clone()]);
+        assert!(cache.take_requests().is_empty());
+
+        cache.fill(&dir, vec![entry("b"), entry("a")], 100);
+        let names = |entries: Option<&[DirEntry]>| -> Vec<String> {
+            entries.unwrap().iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(cache.entries(&dir, 100)), vec!["a", "b"]);
+        assert_eq!(
+            names(cache.entries(&dir, 100 + DIR_CACHE_TTL_SECS - 1)).len(),
+            2
+        );
+        assert!(cache.take_requests().is_empty());
+
+        // A stale listing is still served while it reloads.
+        assert_eq!(
+            names(cache.entries(&dir, 100 + DIR_CACHE_TTL_SECS)).len(),
+            2
+        );
+        assert_eq!(cache.take_requests(), vec![dir.clone()]);
+
+        cache.invalidate(&dir);
+        assert_eq!(cache.entries(&dir, 200), None);
+
+        // Clearing drops outstanding loads too, so they are asked for again.
+        cache.clear();
+        assert_eq!(cache.entries(&dir, 200), None);
+        assert_eq!(cache.take_requests(), vec![dir.clone()]);
+    }
+
+    #[test]
+    fn test_parse_dir_listing() {
+        let base = PathBuf::from("/host/src");
+        assert_eq!(
+            parse_dir_listing(&base, b"./src/app\0./src/my dir\0"),
+            vec![
+                DirEntry {
+                    name: "app".to_string(),
+                    path: base.join("app"),
+                },
+                DirEntry {
+                    name: "my dir".to_string(),
+                    path: base.join("my dir"),
+                },
+            ]
+        );
+        assert!(parse_dir_listing(&base, b"").is_empty());
+        assert_eq!(host_relative_arg(Path::new("/host")), ".");
+        assert_eq!(host_relative_arg(Path::new("/host/src/app")), "./src/app");
+    }
+
     #[test]
     fn test_resolve_workspace_path() {
         assert_eq!(resolve_workspace_path(""), None);